@@ -0,0 +1,600 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::poker::{
+    equity::{range_vs_range, EquityError, RangeEquityOptions},
+    range::{Combo, Range},
+    rng::SeededRng,
+    Card, Rank, Suit,
+};
+
+/// Largest field [`exact`] handles, and where [`icm`] switches to Monte Carlo.
+pub const EXACT_PLAYERS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcmError {
+    NoPlayers,
+    /// Every stack is empty.
+    NoChips,
+    TooManyPlayers(usize),
+    /// The pusher or caller is not a seat, or both are the same seat.
+    InvalidSeat(usize),
+    /// The posted chips do not list one amount per stack.
+    PostedMismatch {
+        stacks: usize,
+        posted: usize,
+    },
+    Equity(EquityError),
+}
+
+impl fmt::Display for IcmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IcmError::NoPlayers => write!(f, "at least one player is needed"),
+            IcmError::NoChips => write!(f, "every stack is empty"),
+            IcmError::TooManyPlayers(players) => write!(
+                f,
+                "{} players are too many for exact ICM, at most {} are allowed",
+                players, EXACT_PLAYERS
+            ),
+            IcmError::InvalidSeat(seat) => write!(f, "seat {} cannot take part", seat),
+            IcmError::PostedMismatch { stacks, posted } => write!(
+                f,
+                "{} posted amounts were given for {} stacks",
+                posted, stacks
+            ),
+            IcmError::Equity(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for IcmError {}
+
+impl From<EquityError> for IcmError {
+    fn from(error: EquityError) -> Self {
+        IcmError::Equity(error)
+    }
+}
+
+/// How [`icm`] approximates large fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IcmOptions {
+    /// Finishing orders dealt when the field is larger than [`EXACT_PLAYERS`].
+    pub trials: usize,
+    pub seed: u64,
+}
+
+impl Default for IcmOptions {
+    fn default() -> Self {
+        IcmOptions {
+            trials: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Tournament equity of every stack under the Independent Chip Model, in the
+/// unit of the payouts. `payouts[0]` goes to the winner, `payouts[1]` to the
+/// runner-up and so on. Exact up to [`EXACT_PLAYERS`] players with chips,
+/// estimated with [`monte_carlo`] beyond that.
+pub fn icm(stacks: &[u64], payouts: &[f64], options: IcmOptions) -> Result<Vec<f64>, IcmError> {
+    if stacks.iter().filter(|stack| **stack > 0).count() <= EXACT_PLAYERS {
+        exact(stacks, payouts)
+    } else {
+        monte_carlo(stacks, payouts, options.trials, options.seed)
+    }
+}
+
+/// Computes ICM equities exactly. Each player takes the next place with a
+/// probability proportional to their stack among the players left (the
+/// Malmuth-Harville model). Players without chips share the last places.
+pub fn exact(stacks: &[u64], payouts: &[f64]) -> Result<Vec<f64>, IcmError> {
+    let (live, mut equities) = split_busted(stacks, payouts)?;
+    if live.len() > EXACT_PLAYERS {
+        return Err(IcmError::TooManyPlayers(live.len()));
+    }
+
+    let chips: Vec<f64> = live.iter().map(|seat| stacks[*seat] as f64).collect();
+    let places = payouts.len().min(live.len());
+
+    // Chance that exactly the players of the mask took the top places.
+    let mut chances = vec![0.0; 1 << live.len()];
+    chances[0] = 1.0;
+    let total: f64 = chips.iter().sum();
+
+    for mask in 0..chances.len() {
+        let placed = (mask as u32).count_ones() as usize;
+        if chances[mask] == 0.0 || placed >= places {
+            continue;
+        }
+
+        let left = total
+            - (0..live.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| chips[i])
+                .sum::<f64>();
+
+        for (i, stack) in chips.iter().enumerate() {
+            if mask & (1 << i) == 0 {
+                let chance = chances[mask] * stack / left;
+                equities[live[i]] += chance * payouts[placed];
+                chances[mask | (1 << i)] += chance;
+            }
+        }
+    }
+
+    Ok(equities)
+}
+
+/// Estimates ICM equities by dealing `trials` random finishing orders under
+/// the same model as [`exact`], for fields too large to enumerate. The same
+/// seed always gives the same result.
+pub fn monte_carlo(
+    stacks: &[u64],
+    payouts: &[f64],
+    trials: usize,
+    seed: u64,
+) -> Result<Vec<f64>, IcmError> {
+    let (live, mut equities) = split_busted(stacks, payouts)?;
+    let places = payouts.len().min(live.len());
+    if places == 0 || trials == 0 {
+        return Ok(equities);
+    }
+
+    let mut rng = SeededRng::new(seed);
+    let mut totals = vec![0.0; live.len()];
+    let mut order: Vec<(f64, usize)> = Vec::with_capacity(live.len());
+
+    for _ in 0..trials {
+        // Each player finishes at an exponential time with a rate of their
+        // stack, so the first one to finish wins with a chance proportional
+        // to their stack, and so on down the places.
+        order.clear();
+        for (i, seat) in live.iter().enumerate() {
+            let uniform = ((rng.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+            order.push((-uniform.ln() / stacks[*seat] as f64, i));
+        }
+
+        if places < order.len() {
+            order.select_nth_unstable_by(places - 1, |a, b| a.0.total_cmp(&b.0));
+        }
+        order[..places].sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        for (place, (_, i)) in order[..places].iter().enumerate() {
+            totals[*i] += payouts[place];
+        }
+    }
+
+    for (i, seat) in live.iter().enumerate() {
+        equities[*seat] = totals[i] / trials as f64;
+    }
+
+    Ok(equities)
+}
+
+// Seats with chips, along with equities where the players without chips
+// already share the places below the live players.
+fn split_busted(stacks: &[u64], payouts: &[f64]) -> Result<(Vec<usize>, Vec<f64>), IcmError> {
+    if stacks.is_empty() {
+        return Err(IcmError::NoPlayers);
+    }
+
+    let live: Vec<usize> = (0..stacks.len()).filter(|i| stacks[*i] > 0).collect();
+    if live.is_empty() {
+        return Err(IcmError::NoChips);
+    }
+
+    let mut equities = vec![0.0; stacks.len()];
+    let busted = stacks.len() - live.len();
+    if busted > 0 {
+        let share = payouts
+            .iter()
+            .take(stacks.len())
+            .skip(live.len())
+            .sum::<f64>()
+            / busted as f64;
+
+        for (i, stack) in stacks.iter().enumerate() {
+            if *stack == 0 {
+                equities[i] = share;
+            }
+        }
+    }
+
+    Ok((live, equities))
+}
+
+/// A push/fold decision between two players once everyone else has folded,
+/// e.g. the small blind against the big blind. When the pusher folds, the
+/// pot goes to the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFoldSpot {
+    /// Chips of every seat before the hand, blinds and antes included.
+    pub stacks: Vec<u64>,
+    /// Chips every seat already put in the pot, i.e. blinds and antes.
+    pub posted: Vec<u64>,
+    pub payouts: Vec<f64>,
+    pub pusher: usize,
+    pub caller: usize,
+}
+
+/// How the push/fold charts compute ICM and the preflop equities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PushFoldOptions {
+    pub icm: IcmOptions,
+    /// Preflop equities need `runouts`, walking every board is too slow. Each
+    /// chart plays every combo against the opposing range that many times, so
+    /// the cost grows with both: the default of 20 takes about 3 s on one
+    /// core in a release build against two thirds of the hands.
+    pub equity: RangeEquityOptions,
+}
+
+impl Default for PushFoldOptions {
+    fn default() -> Self {
+        PushFoldOptions {
+            icm: IcmOptions::default(),
+            equity: RangeEquityOptions {
+                runouts: Some(20),
+                ..RangeEquityOptions::default()
+            },
+        }
+    }
+}
+
+/// Tournament equity of playing a starting hand and of folding it, averaged
+/// over its combos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandDecision {
+    /// The starting hand, e.g. `AKs` or `77`.
+    pub hand: String,
+    pub play: f64,
+    pub fold: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFoldChart {
+    /// Starting hands worth playing.
+    pub range: Range,
+    /// Every starting hand from `AA` down, with both equities.
+    pub hands: Vec<HandDecision>,
+}
+
+/// The hands the pusher should move all-in with when the caller calls with
+/// `calling`. Pushing is worth it when its ICM equity beats folding.
+pub fn push_chart(
+    spot: &PushFoldSpot,
+    calling: &Range,
+    options: PushFoldOptions,
+) -> Result<PushFoldChart, IcmError> {
+    let outcomes = Outcomes::new(spot, options.icm)?;
+    let equities = get_combo_equities(calling, options.equity)?;
+    let pusher = spot.pusher;
+
+    Ok(to_chart(|combo| {
+        let cards = combo.cards();
+        let unblocked = calling.without_cards(&cards).total_weight();
+        let call_chance = unblocked / 1225.0;
+        let fold = outcomes.pusher_folds[pusher];
+
+        if call_chance == 0.0 {
+            return Some((outcomes.caller_folds[pusher], fold));
+        }
+
+        let equity = equities.get(&combo)?;
+        let called =
+            equity * outcomes.pusher_wins[pusher] + (1.0 - equity) * outcomes.caller_wins[pusher];
+
+        Some((
+            (1.0 - call_chance) * outcomes.caller_folds[pusher] + call_chance * called,
+            fold,
+        ))
+    }))
+}
+
+/// The hands the caller should call with when the pusher moves all-in with
+/// `pushing`.
+pub fn call_chart(
+    spot: &PushFoldSpot,
+    pushing: &Range,
+    options: PushFoldOptions,
+) -> Result<PushFoldChart, IcmError> {
+    let outcomes = Outcomes::new(spot, options.icm)?;
+    let equities = get_combo_equities(pushing, options.equity)?;
+    let caller = spot.caller;
+
+    Ok(to_chart(|combo| {
+        let equity = equities.get(&combo)?;
+
+        Some((
+            equity * outcomes.caller_wins[caller] + (1.0 - equity) * outcomes.pusher_wins[caller],
+            outcomes.caller_folds[caller],
+        ))
+    }))
+}
+
+/// Works out both charts by letting each player answer the other's last
+/// chart, starting with a caller that calls with everything.
+pub fn push_fold_charts(
+    spot: &PushFoldSpot,
+    iterations: usize,
+    options: PushFoldOptions,
+) -> Result<(PushFoldChart, PushFoldChart), IcmError> {
+    let mut calling = get_starting_hands()
+        .iter()
+        .fold(Range::new(), |mut all, hand| {
+            for (combo, weight) in hand.iter() {
+                all.insert(combo, weight);
+            }
+            all
+        });
+
+    let mut push = push_chart(spot, &calling, options)?;
+    let mut call = call_chart(spot, &push.range, options)?;
+
+    for _ in 1..iterations {
+        calling = call.range.clone();
+        push = push_chart(spot, &calling, options)?;
+        call = call_chart(spot, &push.range, options)?;
+    }
+
+    Ok((push, call))
+}
+
+// ICM equities of every seat after each way the hand can end.
+struct Outcomes {
+    pusher_folds: Vec<f64>,
+    caller_folds: Vec<f64>,
+    pusher_wins: Vec<f64>,
+    caller_wins: Vec<f64>,
+}
+
+impl Outcomes {
+    fn new(spot: &PushFoldSpot, options: IcmOptions) -> Result<Self, IcmError> {
+        let players = spot.stacks.len();
+        if spot.posted.len() != players {
+            return Err(IcmError::PostedMismatch {
+                stacks: players,
+                posted: spot.posted.len(),
+            });
+        }
+        for seat in [spot.pusher, spot.caller] {
+            if seat >= players || spot.stacks[seat] == 0 || spot.pusher == spot.caller {
+                return Err(IcmError::InvalidSeat(seat));
+            }
+        }
+
+        let (pusher, caller) = (spot.pusher, spot.caller);
+        let posted: Vec<u64> = spot
+            .stacks
+            .iter()
+            .zip(&spot.posted)
+            .map(|(stack, posted)| (*posted).min(*stack))
+            .collect();
+        let pot: u64 = posted.iter().sum();
+
+        let after_posting: Vec<u64> = spot
+            .stacks
+            .iter()
+            .zip(&posted)
+            .map(|(stack, posted)| stack - posted)
+            .collect();
+        let won_by = |winner: usize| {
+            let mut stacks = after_posting.clone();
+            stacks[winner] += pot;
+            stacks
+        };
+
+        // All-in and called, each puts in up to the shorter stack.
+        let at_risk = spot.stacks[pusher].min(spot.stacks[caller]);
+        let showdown_pot = pot - posted[pusher] - posted[caller] + 2 * at_risk;
+        let showdown_won_by = |winner: usize, loser: usize| {
+            let mut stacks = after_posting.clone();
+            stacks[winner] = spot.stacks[winner] - at_risk + showdown_pot;
+            stacks[loser] = spot.stacks[loser] - at_risk;
+            stacks
+        };
+
+        let equities = |stacks: Vec<u64>| icm(&stacks, &spot.payouts, options);
+
+        Ok(Outcomes {
+            pusher_folds: equities(won_by(caller))?,
+            caller_folds: equities(won_by(pusher))?,
+            pusher_wins: equities(showdown_won_by(pusher, caller))?,
+            caller_wins: equities(showdown_won_by(caller, pusher))?,
+        })
+    }
+}
+
+// Preflop pot share of every combo against the opposing range, as a fraction.
+fn get_combo_equities(
+    opponent: &Range,
+    options: RangeEquityOptions,
+) -> Result<HashMap<Combo, f64>, IcmError> {
+    if opponent.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut every_combo = Range::new();
+    for hand in get_starting_hands() {
+        for (combo, weight) in hand.iter() {
+            every_combo.insert(combo, weight);
+        }
+    }
+
+    let equity = range_vs_range(&every_combo, opponent, &[], &[], options)?;
+
+    Ok(equity
+        .hero_combos
+        .iter()
+        .map(|combo| (combo.combo, combo.equity / 100.0))
+        .collect())
+}
+
+// Averages the play and fold equities of each starting hand over its combos,
+// skipping combos the decision leaves out.
+fn to_chart<F: Fn(Combo) -> Option<(f64, f64)>>(decide: F) -> PushFoldChart {
+    let mut range = Range::new();
+    let mut hands: Vec<HandDecision> = Vec::new();
+
+    for hand in get_starting_hands() {
+        let decisions: Vec<(f64, f64)> =
+            hand.iter().filter_map(|(combo, _)| decide(combo)).collect();
+        if decisions.is_empty() {
+            continue;
+        }
+
+        let count = decisions.len() as f64;
+        let play = decisions.iter().map(|(play, _)| play).sum::<f64>() / count;
+        let fold = decisions.iter().map(|(_, fold)| fold).sum::<f64>() / count;
+
+        if play > fold {
+            for (combo, weight) in hand.iter() {
+                range.insert(combo, weight);
+            }
+        }
+        hands.push(HandDecision {
+            hand: hand.to_string(),
+            play,
+            fold,
+        });
+    }
+
+    PushFoldChart { range, hands }
+}
+
+// The 169 starting hands, each as the range of its combos, from `AA` down:
+// the pair, then the suited and offsuit hands with each lower card.
+fn get_starting_hands() -> Vec<Range> {
+    let mut hands: Vec<Range> = Vec::new();
+
+    for (i, high) in Rank::ALL.iter().enumerate().rev() {
+        let mut pair = Range::new();
+        for (a, first) in Suit::ALL.iter().enumerate() {
+            for second in &Suit::ALL[a + 1..] {
+                pair.insert(
+                    Combo::new(Card::new(*high, *first), Card::new(*high, *second)),
+                    1.0,
+                );
+            }
+        }
+        hands.push(pair);
+
+        for low in Rank::ALL[..i].iter().rev() {
+            let mut suited = Range::new();
+            let mut offsuit = Range::new();
+
+            for first in Suit::ALL {
+                for second in Suit::ALL {
+                    let combo = Combo::new(Card::new(*high, first), Card::new(*low, second));
+                    if first == second {
+                        suited.insert(combo, 1.0);
+                    } else {
+                        offsuit.insert(combo, 1.0);
+                    }
+                }
+            }
+
+            hands.push(suited);
+            hands.push(offsuit);
+        }
+    }
+
+    hands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_matches_the_malmuth_harville_model() {
+        let equities = exact(&[5000, 3000, 2000], &[50.0, 30.0, 20.0]).unwrap();
+
+        for (equity, expected) in equities.iter().zip([38.392857, 32.75, 28.857143]) {
+            assert!(
+                (equity - expected).abs() < 1e-6,
+                "{} != {}",
+                equity,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn exact_shares_out_the_whole_prize_pool() {
+        let stacks = [1200, 800, 3500, 150, 2400, 0, 990];
+        let payouts = [50.0, 25.0, 15.0, 10.0];
+        let equities = exact(&stacks, &payouts).unwrap();
+
+        assert!((equities.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert_eq!(equities[5], 0.0);
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_exact() {
+        let stacks = [4000, 2500, 1800, 1200, 500];
+        let payouts = [50.0, 30.0, 20.0];
+        let exact = exact(&stacks, &payouts).unwrap();
+        let estimate = monte_carlo(&stacks, &payouts, 200_000, 3).unwrap();
+
+        for (exact, estimate) in exact.iter().zip(&estimate) {
+            assert!((exact - estimate).abs() < 0.2, "{} != {}", exact, estimate);
+        }
+    }
+
+    // Heads-up for 10 big blinds with the winner taking it all, so equities
+    // are chip shares: folding the small blind keeps 47.5%, folding the big
+    // blind 45%.
+    fn heads_up() -> PushFoldSpot {
+        PushFoldSpot {
+            stacks: vec![1000, 1000],
+            posted: vec![50, 100],
+            payouts: vec![100.0],
+            pusher: 0,
+            caller: 1,
+        }
+    }
+
+    fn options(runouts: usize) -> PushFoldOptions {
+        PushFoldOptions {
+            equity: RangeEquityOptions {
+                runouts: Some(runouts),
+                seed: 7,
+                threads: 2,
+            },
+            ..PushFoldOptions::default()
+        }
+    }
+
+    fn decision<'a>(chart: &'a PushFoldChart, hand: &str) -> &'a HandDecision {
+        chart
+            .hands
+            .iter()
+            .find(|decision| decision.hand == hand)
+            .unwrap()
+    }
+
+    #[test]
+    fn only_aces_call_a_push_with_aces() {
+        let chart = call_chart(&heads_up(), &"AA".parse().unwrap(), options(200)).unwrap();
+        assert_eq!(chart.range, "AA".parse().unwrap());
+
+        let aces = decision(&chart, "AA");
+        assert!((aces.fold - 45.0).abs() < 1e-9);
+        assert!((aces.play - 50.0).abs() < 2.0, "{}", aces.play);
+
+        let kings = decision(&chart, "KK");
+        assert!((kings.play - 18.0).abs() < 2.0, "{}", kings.play);
+        assert!(kings.play < kings.fold);
+    }
+
+    #[test]
+    fn any_two_cards_push_into_a_tight_caller() {
+        let chart = push_chart(&heads_up(), &"QQ+, AK".parse().unwrap(), options(5)).unwrap();
+        assert_eq!(chart.range.len(), 1326);
+
+        // Rarely called, so taking the blinds outweighs losing when called.
+        let worst = decision(&chart, "72o");
+        assert!((worst.fold - 47.5).abs() < 1e-9);
+        assert!(worst.play > 54.0 && worst.play < 55.0, "{}", worst.play);
+    }
+}
@@ -4,6 +4,7 @@ use time::{Duration, PrimitiveDateTime as DateTime};
 use crate::{clock::Clock, planet::Planet};
 
 pub mod clock;
+pub mod icm;
 pub mod luhn;
 pub mod minesweeper;
 pub mod planet;
@@ -1,320 +1,1181 @@
+pub mod bot;
+pub mod deck;
+pub mod describe;
+pub mod draw;
+pub mod equity;
+pub mod fast;
+pub mod history;
+pub mod limit;
+pub mod notation;
+pub mod outs;
+pub mod pots;
+pub mod range;
+pub mod replay;
+pub mod rng;
+pub mod seat;
+pub mod stud;
+pub mod table;
+pub mod wild;
+
 use std::{
-    cmp::{Ordering, PartialEq},
-    collections::HashMap,
+    cmp::{Ordering, Reverse},
+    collections::HashSet,
+    error::Error,
+    fmt,
+    ops::Add,
+    str::FromStr,
 };
 
-#[derive(Debug)]
-struct Card<'a> {
-    rank: i8,
-    symbol: &'a str,
+use deck::DeckKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Two = 2,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
 }
 
-impl<'a> PartialEq for Card<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.eq(&other_rank_parsed)
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    /// Numeric value of the rank, from 2 for a deuce up to 14 for an ace.
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_value(value: u8) -> Option<Rank> {
+        Rank::ALL.iter().copied().find(|rank| rank.value() == value)
     }
 
-    fn ne(&self, other: &Self) -> bool {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.ne(&other_rank_parsed)
+    fn symbol(self) -> &'static str {
+        match self {
+            Rank::Two => "2",
+            Rank::Three => "3",
+            Rank::Four => "4",
+            Rank::Five => "5",
+            Rank::Six => "6",
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+            Rank::Ace => "A",
+        }
+    }
+
+    // One character per rank, "T" for ten, as in ranges and hand histories.
+    fn short_symbol(self) -> &'static str {
+        match self {
+            Rank::Ten => "T",
+            rank => rank.symbol(),
+        }
+    }
+
+    // Long form name, as in "Ace of Hearts".
+    fn name(self) -> &'static str {
+        match self {
+            Rank::Two => "Two",
+            Rank::Three => "Three",
+            Rank::Four => "Four",
+            Rank::Five => "Five",
+            Rank::Six => "Six",
+            Rank::Seven => "Seven",
+            Rank::Eight => "Eight",
+            Rank::Nine => "Nine",
+            Rank::Ten => "Ten",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+            Rank::Ace => "Ace",
+        }
     }
 }
 
-impl<'a> PartialOrd for Card<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.partial_cmp(&other_rank_parsed)
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Parses a rank symbol such as `10`, `T` or `q`, or its name such as `Ace`,
+/// in any case.
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rank::ALL
+            .iter()
+            .find(|rank| {
+                rank.symbol().eq_ignore_ascii_case(s)
+                    || rank.short_symbol().eq_ignore_ascii_case(s)
+                    || rank.name().eq_ignore_ascii_case(s)
+            })
+            .copied()
+            .ok_or_else(|| ParseCardError::new(s, ParseCardErrorKind::InvalidRank))
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
 
-    fn lt(&self, other: &Self) -> bool {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.lt(&other_rank_parsed)
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Suit::Clubs => "C",
+            Suit::Diamonds => "D",
+            Suit::Hearts => "H",
+            Suit::Spades => "S",
+        }
     }
 
-    fn le(&self, other: &Self) -> bool {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.le(&other_rank_parsed)
+    fn glyph(self) -> char {
+        match self {
+            Suit::Clubs => '♣',
+            Suit::Diamonds => '♦',
+            Suit::Hearts => '♥',
+            Suit::Spades => '♠',
+        }
     }
 
-    fn gt(&self, other: &Self) -> bool {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.gt(&other_rank_parsed)
+    fn name(self) -> &'static str {
+        match self {
+            Suit::Clubs => "Clubs",
+            Suit::Diamonds => "Diamonds",
+            Suit::Hearts => "Hearts",
+            Suit::Spades => "Spades",
+        }
     }
+}
 
-    fn ge(&self, other: &Self) -> bool {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.ge(&other_rank_parsed)
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
     }
 }
 
-impl<'a> Eq for Card<'a> {}
+/// Parses a suit letter such as `H` or `h`, a symbol such as `♥` or `♡`, or
+/// its name such as `Hearts` or `heart`.
+impl FromStr for Suit {
+    type Err = ParseCardError;
 
-impl<'a> Ord for Card<'a> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let rank_parsed = self.rank;
-        let other_rank_parsed = other.rank;
-        rank_parsed.cmp(&other_rank_parsed)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suit = Suit::ALL.iter().copied().find(|suit| {
+            let name = suit.name();
+
+            suit.symbol().eq_ignore_ascii_case(s)
+                || name.eq_ignore_ascii_case(s)
+                || name[..name.len() - 1].eq_ignore_ascii_case(s)
+        });
+
+        match (suit, s) {
+            (Some(suit), _) => Ok(suit),
+            (None, "♣" | "♧") => Ok(Suit::Clubs),
+            (None, "♦" | "♢") => Ok(Suit::Diamonds),
+            (None, "♥" | "♡") => Ok(Suit::Hearts),
+            (None, "♠" | "♤") => Ok(Suit::Spades),
+            _ => Err(ParseCardError::new(s, ParseCardErrorKind::InvalidSuit)),
+        }
     }
 }
 
-#[derive(PartialEq, Debug)]
-enum HandType<'a> {
-    FiveOfKind(&'a str),
-    StraightFlush(&'a str),
-    FourOfKind(&'a str),
-    FullHouse(&'a str),
-    Flush(&'a str),
-    Straight(&'a str),
-    ThreeOfKind(&'a str),
-    TwoPair(&'a str),
-    OnePair(&'a str),
-    HighCard(&'a str),
+/// A single playing card. Cards order by rank first and suit second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
 }
 
-/// Given a list of poker hands, return a list of those hands which win.
-///
-/// Note the type signature: this function should return _the same_ reference to
-/// the winning hand(s) as were passed in, not reconstructed strings which happen to be equal.
-pub fn winning_hands<'a>(hands: &[&'a str]) -> Vec<&'a str> {
-    let mut hand_map: HashMap<i8, Vec<&'a str>> = HashMap::new();
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Self {
+        Card { rank, suit }
+    }
+}
 
-    for hand in hands {
-        let cards = infer_hand_type(hand);
-        let hand_type = get_hand_type(&cards, hand);
-        let hand_rank = get_hand_rank(&hand_type, hand);
-        let hands_vec = hand_map.entry(hand_rank).or_insert(vec![]);
-        hands_vec.push(hand);
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
     }
+}
 
-    let mut strongest_rank = 0;
+/// Parses a card written as rank followed by suit, e.g. `10H`, `Th` or `Q♠`,
+/// as a Unicode playing card such as `🂱`, or in long form such as
+/// `Ace of Hearts`. Ranks and suits are read in any case.
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (rank, suit) = match words.as_slice() {
+            [rank, of, suit] if of.eq_ignore_ascii_case("of") => (*rank, *suit),
+            [token] => match token.char_indices().last() {
+                Some((0, symbol)) => return notation::parse_playing_card(symbol, s),
+                Some((i, _)) => token.split_at(i),
+                None => return Err(ParseCardError::new(s, ParseCardErrorKind::Malformed)),
+            },
+            _ => return Err(ParseCardError::new(s, ParseCardErrorKind::Malformed)),
+        };
 
-    for rank in hand_map.keys() {
-        if *rank > strongest_rank {
-            strongest_rank = *rank;
+        let rank = match rank.parse::<Rank>() {
+            Ok(rank) => rank,
+            Err(_) => return Err(ParseCardError::new(s, ParseCardErrorKind::InvalidRank)),
+        };
+        let suit = match suit.parse::<Suit>() {
+            Ok(suit) => suit,
+            Err(_) => return Err(ParseCardError::new(s, ParseCardErrorKind::InvalidSuit)),
+        };
+
+        Ok(Card::new(rank, suit))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseCardErrorKind {
+    /// The token is too short to hold both a rank and a suit.
+    Malformed,
+    InvalidRank,
+    InvalidSuit,
+}
+
+/// Error returned when a card token cannot be parsed. The position is the
+/// zero-based index of the token within its hand, and 0 for a lone card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCardError {
+    token: String,
+    position: usize,
+    kind: ParseCardErrorKind,
+}
+
+impl ParseCardError {
+    fn new(token: &str, kind: ParseCardErrorKind) -> Self {
+        ParseCardError {
+            token: token.to_string(),
+            position: 0,
+            kind,
         }
     }
 
-    let hands = hand_map.get(&strongest_rank).unwrap().to_vec();
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn kind(&self) -> ParseCardErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            ParseCardErrorKind::Malformed => "malformed card",
+            ParseCardErrorKind::InvalidRank => "invalid rank in card",
+            ParseCardErrorKind::InvalidSuit => "invalid suit in card",
+        };
+
+        write!(
+            f,
+            "{} {:?} at position {}",
+            problem, self.token, self.position
+        )
+    }
+}
 
-    let mut strongest_hands: Vec<&str> = Vec::new();
-    let mut highest = 0;
+impl Error for ParseCardError {}
 
-    for hand in hands {
-        let card_rank = get_hand_power(hand);
+/// Parses a hand such as `"4S 5H 10D TC AS"`. Cards are separated by any
+/// whitespace or commas and may use any notation [`Card`] parses, e.g.
+/// `"A♥, 🂮, Queen of Clubs"`.
+pub fn parse_hand(hand: &str) -> Result<Vec<Card>, ParseCardError> {
+    let words: Vec<&str> = hand
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .collect();
 
-        if card_rank > highest {
-            highest = card_rank;
-            strongest_hands = vec![hand];
-        } else if card_rank == highest {
-            if get_card_sum(hand) < get_card_sum(strongest_hands[0]) {
-                strongest_hands = vec![strongest_hands[0]]
-            } else {
-                strongest_hands.push(hand)
+    let mut cards: Vec<Card> = Vec::new();
+    let mut i = 0;
+
+    while i < words.len() {
+        // Long form cards span three words.
+        let token = match words.get(i + 1) {
+            Some(of) if of.eq_ignore_ascii_case("of") && i + 2 < words.len() => {
+                i += 3;
+                words[i - 3..i].join(" ")
+            }
+            _ => {
+                i += 1;
+                words[i - 1].to_string()
+            }
+        };
+
+        match token.parse::<Card>() {
+            Ok(card) => cards.push(card),
+            Err(mut err) => {
+                err.position = cards.len();
+                return Err(err);
             }
         }
     }
 
-    return strongest_hands;
+    Ok(cards)
+}
+
+/// Hand categories, from the weakest to the strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfKind,
+    StraightFlush,
+    FiveOfKind,
+}
+
+/// The strength of a hand: its category followed by the ranks that break ties
+/// within it. Pairs, trips and quads come first, highest group first, then
+/// the kickers in descending order. Straights carry only their top card, so a
+/// wheel (A-2-3-4-5) is a five high straight.
+///
+/// Hand ranks compare the way poker hands do, the greater one wins.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandRank {
+    hand_type: HandType,
+    ranks: Vec<Rank>,
+}
+
+impl HandRank {
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
+    }
+
+    pub fn ranks(&self) -> &[Rank] {
+        &self.ranks
+    }
 }
 
-fn get_hand_power<'a>(hand: &'a str) -> i8 {
-    let cards = infer_hand_type(hand);
-    let hand_type = get_hand_type(&cards, hand);
+/// Evaluates a five card hand.
+pub fn evaluate(hand: &[Card]) -> HandRank {
+    RankingRules::High.evaluate(hand)
+}
 
-    let first_result = (cards[0].rank - cards[1].rank).abs();
-    let second_result = (cards[1].rank - cards[2].rank).abs();
-    let third_result = (cards[2].rank - cards[3].rank).abs();
-    let fourth_result = (cards[3].rank - cards[4].rank).abs();
+/// Ranks an incomplete hand of one to five cards, e.g. the up-cards showing
+/// in seven card stud. Fewer than five cards never make a straight or a
+/// flush, so only pairs, trips and quads count, then the high cards.
+pub fn evaluate_partial(cards: &[Card]) -> HandRank {
+    assert!(
+        (1..=5).contains(&cards.len()),
+        "a partial hand has one to five cards"
+    );
 
-    if hand_type == HandType::TwoPair(hand) {
-        if first_result == 0 && second_result != 0 && third_result == 0 && fourth_result != 0 {
-            return cards[0].rank + cards[1].rank + cards[2].rank + cards[3].rank;
-        } else if first_result != 0 && second_result == 0 && third_result != 0 && fourth_result == 0
-        {
-            return cards[2].rank + cards[3].rank + cards[3].rank + cards[4].rank;
-        } else if first_result == 0 && second_result != 0 && third_result != 0 && fourth_result == 0
-        {
-            return cards[0].rank + cards[1].rank + cards[3].rank + cards[4].rank;
+    get_hand_rank(RankingRules::High, cards)
+}
+
+/// How hands are ranked against each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RankingRules {
+    /// Regular high poker, the wheel A-2-3-4-5 counts as a straight.
+    #[default]
+    High,
+    /// Deuce-to-seven lowball: the worst high hand wins, aces are always
+    /// high and straights and flushes count against the hand.
+    DeuceToSeven,
+    /// Ace-to-five lowball: aces are low, straights and flushes are ignored
+    /// and the lowest hand wins, so A-2-3-4-5 is the best hand.
+    AceToFive,
+    /// Short-deck (6+) poker, played without the deuces through fives: a
+    /// flush beats a full house and A-6-7-8-9 is the lowest straight.
+    ShortDeck,
+}
+
+impl RankingRules {
+    /// Evaluates a five card hand under these rules. Hand ranks from lowball
+    /// and short-deck rules must be compared with [`RankingRules::compare`],
+    /// not with `Ord`.
+    pub fn evaluate(self, hand: &[Card]) -> HandRank {
+        assert_eq!(hand.len(), 5, "a poker hand has exactly five cards");
+
+        get_hand_rank(self, hand)
+    }
+
+    /// Compares two hand ranks evaluated under these rules. `Greater` means
+    /// the first hand wins.
+    pub fn compare(self, a: &HandRank, b: &HandRank) -> Ordering {
+        match self {
+            RankingRules::High => a.cmp(b),
+            RankingRules::ShortDeck => self
+                .category_strength(a.hand_type)
+                .cmp(&self.category_strength(b.hand_type))
+                .then_with(|| a.ranks.cmp(&b.ranks)),
+            RankingRules::DeuceToSeven => b.cmp(a),
+            RankingRules::AceToFive => {
+                let a_values = a.ranks.iter().map(|rank| get_rank_value(self, *rank));
+                let b_values = b.ranks.iter().map(|rank| get_rank_value(self, *rank));
+
+                b.hand_type
+                    .cmp(&a.hand_type)
+                    .then_with(|| b_values.cmp(a_values))
+            }
+        }
+    }
+
+    /// The deck the game is dealt from.
+    pub fn deck_kind(self) -> DeckKind {
+        match self {
+            RankingRules::ShortDeck => DeckKind::ShortDeck,
+            _ => DeckKind::Standard,
+        }
+    }
+
+    /// Picks the strongest five card hand under these rules that can be made
+    /// from five or more cards.
+    pub fn best_hand(self, cards: &[Card]) -> (HandRank, [Card; 5]) {
+        assert!(
+            cards.len() >= 5,
+            "at least five cards are needed to make a hand"
+        );
+
+        let mut best: Option<(HandRank, [Card; 5])> = None;
+
+        for_each_combination(cards.len(), 5, |indexes| {
+            let hand = [
+                cards[indexes[0]],
+                cards[indexes[1]],
+                cards[indexes[2]],
+                cards[indexes[3]],
+                cards[indexes[4]],
+            ];
+            let rank = get_hand_rank(self, &hand);
+
+            if best
+                .as_ref()
+                .is_none_or(|(best_rank, _)| self.compare(&rank, best_rank) == Ordering::Greater)
+            {
+                best = Some((rank, hand));
+            }
+        });
+
+        best.unwrap()
+    }
+
+    fn counts_straights_and_flushes(self) -> bool {
+        self != RankingRules::AceToFive
+    }
+
+    // Order of the hand categories, the greater the stronger.
+    fn category_strength(self, hand_type: HandType) -> u8 {
+        match (self, hand_type) {
+            (RankingRules::ShortDeck, HandType::Flush) => HandType::FullHouse as u8,
+            (RankingRules::ShortDeck, HandType::FullHouse) => HandType::Flush as u8,
+            _ => hand_type as u8,
         }
     }
+}
 
-    get_card_sum(hand)
+/// Picks the strongest five card hand that can be made from five or more cards,
+/// e.g. two hole cards and the board in Texas Hold'em.
+pub fn best_hand(cards: &[Card]) -> (HandRank, [Card; 5]) {
+    RankingRules::High.best_hand(cards)
 }
 
-fn infer_hand_type<'a>(original_hand: &'a str) -> Vec<Card> {
-    let cards: Vec<&'a str> = original_hand.split(" ").collect();
+/// Returns the seats holding the best Hold'em hand on the given board. Every
+/// tied seat is returned, in seat order, when the pot is split.
+pub fn holdem_winners(board: &[Card], hole_cards_per_player: &[[Card; 2]]) -> Vec<usize> {
+    holdem_winners_with(RankingRules::High, board, hole_cards_per_player)
+}
 
-    let mut hand: Vec<Card> = Vec::new();
+/// Same as [`holdem_winners`], with the hands ranked under the given rules,
+/// e.g. short-deck Hold'em.
+pub fn holdem_winners_with(
+    rules: RankingRules,
+    board: &[Card],
+    hole_cards_per_player: &[[Card; 2]],
+) -> Vec<usize> {
+    let ranks: Vec<HandRank> = hole_cards_per_player
+        .iter()
+        .map(|hole_cards| {
+            let mut cards = hole_cards.to_vec();
+            cards.extend_from_slice(board);
+            rules.best_hand(&cards).0
+        })
+        .collect();
 
-    for card in cards {
-        let mut splitted_card: Vec<&str> = card.split("").collect();
-        splitted_card = splitted_card[1..splitted_card.len() - 1].to_vec();
+    get_best_seats_with(rules, &ranks)
+}
 
-        let card = if splitted_card.len() > 2 {
-            (
-                format!("{}{}", splitted_card[0], splitted_card[1]),
-                splitted_card[2],
-            )
-        } else {
-            (splitted_card[0].to_string(), splitted_card[1])
-        };
+/// Picks the strongest Omaha hand, which must use exactly two of the four hole
+/// cards and exactly three cards from the board.
+pub fn omaha_best_hand(hole_cards: &[Card; 4], board: &[Card]) -> (HandRank, [Card; 5]) {
+    assert!(
+        board.len() >= 3,
+        "an Omaha hand needs at least three board cards"
+    );
+
+    let mut best: Option<(HandRank, [Card; 5])> = None;
+
+    for_each_combination(4, 2, |hole| {
+        for_each_combination(board.len(), 3, |common| {
+            let hand = [
+                hole_cards[hole[0]],
+                hole_cards[hole[1]],
+                board[common[0]],
+                board[common[1]],
+                board[common[2]],
+            ];
+            let rank = get_hand_rank(RankingRules::High, &hand);
+
+            if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+                best = Some((rank, hand));
+            }
+        });
+    });
+
+    best.unwrap()
+}
+
+/// Returns the seats holding the best Omaha Hi hand on the given board, with
+/// every tied seat included.
+pub fn omaha_winners(board: &[Card], hole_cards_per_player: &[[Card; 4]]) -> Vec<usize> {
+    let ranks: Vec<HandRank> = hole_cards_per_player
+        .iter()
+        .map(|hole_cards| omaha_best_hand(hole_cards, board).0)
+        .collect();
+
+    get_best_seats(&ranks)
+}
+
+/// Picks the best ace-to-five low that qualifies for an eight-or-better split,
+/// i.e. five unpaired cards of eight or lower, from five or more cards.
+pub fn best_low_hand(cards: &[Card]) -> Option<(HandRank, [Card; 5])> {
+    let mut best: Option<(HandRank, [Card; 5])> = None;
+
+    for_each_combination(cards.len(), 5, |indexes| {
+        let hand = [
+            cards[indexes[0]],
+            cards[indexes[1]],
+            cards[indexes[2]],
+            cards[indexes[3]],
+            cards[indexes[4]],
+        ];
+        keep_better_low(&mut best, hand);
+    });
+
+    best
+}
 
-        let card = Card {
-            rank: get_rank_by_char(&card.0, original_hand),
-            symbol: card.1,
+/// Same as [`best_low_hand`] with Omaha's exactly-two-hole-cards rule.
+pub fn omaha_best_low_hand(
+    hole_cards: &[Card; 4],
+    board: &[Card],
+) -> Option<(HandRank, [Card; 5])> {
+    let mut best: Option<(HandRank, [Card; 5])> = None;
+
+    for_each_combination(4, 2, |hole| {
+        for_each_combination(board.len(), 3, |common| {
+            let hand = [
+                hole_cards[hole[0]],
+                hole_cards[hole[1]],
+                board[common[0]],
+                board[common[1]],
+                board[common[2]],
+            ];
+            keep_better_low(&mut best, hand);
+        });
+    });
+
+    best
+}
+
+fn keep_better_low(best: &mut Option<(HandRank, [Card; 5])>, hand: [Card; 5]) {
+    let rules = RankingRules::AceToFive;
+    let rank = rules.evaluate(&hand);
+
+    let qualifies = rank.hand_type == HandType::HighCard
+        && rank
+            .ranks
+            .iter()
+            .all(|rank| get_rank_value(rules, *rank) <= 8);
+
+    if qualifies
+        && best
+            .as_ref()
+            .is_none_or(|(best_rank, _)| rules.compare(&rank, best_rank) == Ordering::Greater)
+    {
+        *best = Some((rank, hand));
+    }
+}
+
+/// Outcome of a high/low split pot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HiLoResult {
+    pub high_winners: Vec<usize>,
+    /// Empty when nobody has a qualifying low, the high hand then scoops.
+    pub low_winners: Vec<usize>,
+    /// Fraction of the pot won by each player, in seat order.
+    pub shares: Vec<Ratio>,
+}
+
+/// Splits a pot between the best high hands and the best qualifying lows.
+/// `lows` holds each player's qualifying low, as returned by
+/// [`best_low_hand`] or [`omaha_best_low_hand`].
+pub fn hi_lo_split(highs: &[HandRank], lows: &[Option<HandRank>]) -> HiLoResult {
+    assert_eq!(
+        highs.len(),
+        lows.len(),
+        "every player needs a high and a low"
+    );
+
+    let high_winners = get_best_seats(highs);
+
+    let mut low_winners: Vec<usize> = Vec::new();
+    let mut best_low: Option<&HandRank> = None;
+
+    for (seat, low) in lows.iter().enumerate() {
+        let low = match low {
+            Some(low) => low,
+            None => continue,
         };
 
-        hand.push(card);
+        match best_low.map(|best| RankingRules::AceToFive.compare(low, best)) {
+            Some(Ordering::Less) => continue,
+            Some(Ordering::Equal) => low_winners.push(seat),
+            _ => {
+                best_low = Some(low);
+                low_winners = vec![seat];
+            }
+        }
+    }
+
+    let mut shares = vec![Ratio::new(0, 1); highs.len()];
+    let halves = if low_winners.is_empty() { 1 } else { 2 };
+
+    for seat in &high_winners {
+        shares[*seat] = shares[*seat] + Ratio::new(1, (halves * high_winners.len()) as u64);
+    }
+    for seat in &low_winners {
+        shares[*seat] = shares[*seat] + Ratio::new(1, (halves * low_winners.len()) as u64);
     }
 
-    hand.sort();
-    hand.reverse();
+    HiLoResult {
+        high_winners,
+        low_winners,
+        shares,
+    }
+}
+
+/// Resolves an Omaha Hi/Lo showdown.
+pub fn omaha_hi_lo_winners(board: &[Card], hole_cards_per_player: &[[Card; 4]]) -> HiLoResult {
+    let highs: Vec<HandRank> = hole_cards_per_player
+        .iter()
+        .map(|hole_cards| omaha_best_hand(hole_cards, board).0)
+        .collect();
+    let lows: Vec<Option<HandRank>> = hole_cards_per_player
+        .iter()
+        .map(|hole_cards| omaha_best_low_hand(hole_cards, board).map(|(rank, _)| rank))
+        .collect();
 
-    hand
+    hi_lo_split(&highs, &lows)
 }
 
-fn get_hand_type<'a>(hand: &Vec<Card>, original_hand: &'a str) -> HandType<'a> {
-    let first_result = (hand[0].rank - hand[1].rank).abs();
-    let second_result = (hand[1].rank - hand[2].rank).abs();
-    let third_result = (hand[2].rank - hand[3].rank).abs();
-    let fourth_result = (hand[3].rank - hand[4].rank).abs();
+/// Resolves a Stud Hi/Lo showdown, where each player plays their best five of
+/// seven cards for both halves.
+pub fn stud_hi_lo_winners(hands: &[Vec<Card>]) -> HiLoResult {
+    let highs: Vec<HandRank> = hands.iter().map(|cards| best_hand(cards).0).collect();
+    let lows: Vec<Option<HandRank>> = hands
+        .iter()
+        .map(|cards| best_low_hand(cards).map(|(rank, _)| rank))
+        .collect();
 
-    if first_result == 0 && second_result == 0 && third_result == 0 && fourth_result == 0 {
-        HandType::FiveOfKind(original_hand)
-    } else if first_result == second_result
-        && second_result == third_result
-        && third_result == fourth_result
-        && is_hand_same_symbol(hand)
-    {
-        HandType::StraightFlush(original_hand)
-    } else if (first_result == 0 && second_result == 0 && third_result == 0)
-        || (second_result == 0 && third_result == 0 && fourth_result == 0)
-    {
-        HandType::FourOfKind(original_hand)
-    } else if (first_result == 0 && second_result == 0 && third_result != 0 && fourth_result == 0)
-        || (fourth_result == 0 && third_result == 0 && second_result != 0 && first_result == 0)
-    {
-        HandType::FullHouse(original_hand)
-    } else if is_hand_same_symbol(hand) {
-        HandType::Flush(original_hand)
-    } else if first_result == second_result
-        && second_result == third_result
-        && third_result == fourth_result
-    {
-        HandType::Straight(original_hand)
-    } else if (first_result == 0 && second_result == 0 && third_result != 0 && fourth_result != 0)
-        || (first_result != 0 && second_result != 0 && third_result == 0 && fourth_result == 0)
-    {
-        HandType::ThreeOfKind(original_hand)
-    } else if (first_result == 0 && second_result != 0 && third_result == 0 && fourth_result != 0)
-        || (first_result != 0 && second_result == 0 && third_result != 0 && fourth_result == 0)
-        || (first_result == 0 && second_result != 0 && third_result != 0 && fourth_result == 0)
-    {
-        HandType::TwoPair(original_hand)
-    } else if first_result == 0 || second_result == 0 || third_result == 0 || fourth_result == 0 {
-        HandType::OnePair(original_hand)
-    } else {
-        HandType::HighCard(original_hand)
+    hi_lo_split(&highs, &lows)
+}
+
+/// An exact non-negative fraction, always kept in lowest terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Ratio {
+    pub fn new(numerator: u64, denominator: u64) -> Self {
+        assert!(denominator != 0, "a ratio cannot have a zero denominator");
+
+        let divisor = gcd(numerator, denominator);
+
+        Ratio {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub fn numerator(&self) -> u64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u64 {
+        self.denominator
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+impl Add for Ratio {
+    type Output = Ratio;
+
+    fn add(self, other: Ratio) -> Ratio {
+        let denominator =
+            self.denominator / gcd(self.denominator, other.denominator) * other.denominator;
+
+        Ratio::new(
+            self.numerator * (denominator / self.denominator)
+                + other.numerator * (denominator / other.denominator),
+            denominator,
+        )
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numerator as u128 * other.denominator as u128)
+            .cmp(&(other.numerator as u128 * self.denominator as u128))
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
     }
 }
 
-fn get_rank_by_char<'a>(c: &'a str, hand: &'a str) -> i8 {
-    if c == "J" {
-        11
-    } else if c == "Q" {
-        12
-    } else if c == "K" {
-        13
-    } else if c == "A" {
-        is_small_or_big(c, hand, 14, 1)
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a.max(1)
     } else {
-        c.parse::<i8>().unwrap()
+        gcd(b, a % b)
     }
 }
 
-fn is_small_or_big<'a>(symbol: &str, hand: &'a str, high: i8, low: i8) -> i8 {
-    let cards: Vec<&str> = hand.split(" ").collect();
+fn get_best_seats(ranks: &[HandRank]) -> Vec<usize> {
+    get_best_seats_with(RankingRules::High, ranks)
+}
 
-    let mut ace = false;
+fn get_best_seats_with(rules: RankingRules, ranks: &[HandRank]) -> Vec<usize> {
+    let best = match ranks.iter().max_by(|a, b| rules.compare(a, b)) {
+        Some(best) => best,
+        None => return Vec::new(),
+    };
 
-    for card in cards {
-        let mut splitted_card: Vec<&str> = card.split("").collect();
-        splitted_card = splitted_card[1..splitted_card.len() - 1].to_vec();
+    ranks
+        .iter()
+        .enumerate()
+        .filter(|(_, rank)| rules.compare(rank, best) == Ordering::Equal)
+        .map(|(seat, _)| seat)
+        .collect()
+}
 
-        let card = if splitted_card.len() > 2 {
-            (
-                format!("{}{}", splitted_card[0], splitted_card[1]),
-                splitted_card[2],
-            )
-        } else {
-            (splitted_card[0].to_string(), splitted_card[1])
-        };
+// Calls `f` with every set of `k` indexes out of `0..n`, in lexicographic order.
+fn for_each_combination<F: FnMut(&[usize])>(n: usize, k: usize, mut f: F) {
+    if k > n {
+        return;
+    }
+
+    let mut indexes: Vec<usize> = (0..k).collect();
+
+    loop {
+        f(&indexes);
+
+        let mut i = k;
+        while i > 0 && indexes[i - 1] == i - 1 + n - k {
+            i -= 1;
+        }
+        if i == 0 {
+            return;
+        }
+
+        indexes[i - 1] += 1;
+        for j in i..k {
+            indexes[j] = indexes[j - 1] + 1;
+        }
+    }
+}
+
+/// Error returned by [`try_winning_hands`]. Every variant carries the index of
+/// the offending hand within the input slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandError {
+    WrongCardCount {
+        hand: usize,
+        count: usize,
+    },
+    DuplicateCard {
+        hand: usize,
+        card: Card,
+    },
+    UnknownRank {
+        hand: usize,
+        token: String,
+    },
+    UnknownSuit {
+        hand: usize,
+        token: String,
+    },
+    /// A token too short to hold both a rank and a suit, e.g. `H`.
+    Malformed {
+        hand: usize,
+        token: String,
+    },
+}
+
+impl HandError {
+    /// Index of the hand that caused the error.
+    pub fn hand(&self) -> usize {
+        match self {
+            HandError::WrongCardCount { hand, .. }
+            | HandError::DuplicateCard { hand, .. }
+            | HandError::UnknownRank { hand, .. }
+            | HandError::UnknownSuit { hand, .. }
+            | HandError::Malformed { hand, .. } => *hand,
+        }
+    }
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandError::WrongCardCount { hand, count } => {
+                write!(f, "hand {} has {} cards, expected 5", hand, count)
+            }
+            HandError::DuplicateCard { hand, card } => {
+                write!(f, "hand {} repeats the card {}", hand, card)
+            }
+            HandError::UnknownRank { hand, token } => {
+                write!(
+                    f,
+                    "hand {} has a card with an unknown rank: {:?}",
+                    hand, token
+                )
+            }
+            HandError::UnknownSuit { hand, token } => {
+                write!(
+                    f,
+                    "hand {} has a card with an unknown suit: {:?}",
+                    hand, token
+                )
+            }
+            HandError::Malformed { hand, token } => {
+                write!(f, "hand {} has a malformed card: {:?}", hand, token)
+            }
+        }
+    }
+}
+
+impl Error for HandError {}
+
+/// Given a list of poker hands, return a list of those hands which win.
+///
+/// Note the type signature: this function should return _the same_ reference to
+/// the winning hand(s) as were passed in, not reconstructed strings which happen to be equal.
+///
+/// Panics if any hand is malformed, see [`try_winning_hands`] for the fallible version.
+/// The same card may appear more than once, within a hand or across hands.
+pub fn winning_hands<'a>(hands: &[&'a str]) -> Vec<&'a str> {
+    winning_hands_with(RankingRules::High, hands)
+}
+
+/// Same as [`winning_hands`], with the hands ranked under the given rules.
+pub fn winning_hands_with<'a>(rules: RankingRules, hands: &[&'a str]) -> Vec<&'a str> {
+    match parse_hands(hands, false) {
+        Ok(cards) => pick_winning_hands(rules, hands, &cards),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// Same as [`winning_hands`], but reports malformed hands instead of panicking.
+/// Hands are treated as dealt from a single deck, so a card repeated across
+/// hands is an error. An empty slice of hands has no winners.
+pub fn try_winning_hands<'a>(hands: &[&'a str]) -> Result<Vec<&'a str>, HandError> {
+    let cards = parse_hands(hands, true)?;
+
+    Ok(pick_winning_hands(RankingRules::High, hands, &cards))
+}
+
+fn pick_winning_hands<'a>(
+    rules: RankingRules,
+    hands: &[&'a str],
+    cards: &[Vec<Card>],
+) -> Vec<&'a str> {
+    let mut strongest_hands: Vec<&'a str> = Vec::new();
+    let mut strongest_rank: Option<HandRank> = None;
+
+    for (hand, cards) in hands.iter().zip(cards) {
+        let hand_rank = rules.evaluate(cards);
 
-        if card.0 == symbol {
-            ace = true;
+        match strongest_rank
+            .as_ref()
+            .map(|rank| rules.compare(&hand_rank, rank))
+        {
+            Some(Ordering::Less) => continue,
+            Some(Ordering::Equal) => strongest_hands.push(hand),
+            _ => {
+                strongest_rank = Some(hand_rank);
+                strongest_hands = vec![hand];
+            }
         }
+    }
+
+    strongest_hands
+}
+
+// Parses every hand, checking that each has five cards and, when `unique` is
+// set, that no card is dealt twice.
+fn parse_hands(hands: &[&str], unique: bool) -> Result<Vec<Vec<Card>>, HandError> {
+    let mut seen: HashSet<Card> = HashSet::new();
+    let mut parsed: Vec<Vec<Card>> = Vec::with_capacity(hands.len());
 
-        let parsed_card_number = match card.0.parse::<i8>() {
-            Ok(num) => num,
-            Err(_) => continue,
+    for (index, hand) in hands.iter().enumerate() {
+        let cards = match parse_hand(hand) {
+            Ok(cards) => cards,
+            Err(err) => {
+                let token = err.token().to_string();
+
+                return Err(match err.kind() {
+                    ParseCardErrorKind::Malformed => HandError::Malformed { hand: index, token },
+                    ParseCardErrorKind::InvalidRank => {
+                        HandError::UnknownRank { hand: index, token }
+                    }
+                    ParseCardErrorKind::InvalidSuit => {
+                        HandError::UnknownSuit { hand: index, token }
+                    }
+                });
+            }
         };
 
-        if ace && parsed_card_number == 2 {
-            return low;
+        if cards.len() != 5 {
+            return Err(HandError::WrongCardCount {
+                hand: index,
+                count: cards.len(),
+            });
+        }
+
+        for card in &cards {
+            if unique && !seen.insert(*card) {
+                return Err(HandError::DuplicateCard {
+                    hand: index,
+                    card: *card,
+                });
+            }
+        }
+        parsed.push(cards);
+    }
+
+    Ok(parsed)
+}
+
+fn get_hand_rank(rules: RankingRules, hand: &[Card]) -> HandRank {
+    let hand_type = get_hand_type(rules, hand);
+
+    let ranks = match hand_type {
+        HandType::StraightFlush | HandType::Straight => {
+            vec![get_straight_high(rules, hand).unwrap()]
         }
+        _ => get_rank_groups(rules, hand)
+            .iter()
+            .map(|(_, rank)| *rank)
+            .collect(),
+    };
+
+    HandRank { hand_type, ranks }
+}
+
+fn get_hand_type(rules: RankingRules, hand: &[Card]) -> HandType {
+    let groups = get_rank_groups(rules, hand);
+    let largest = groups[0].0;
+    let second = groups.get(1).map_or(0, |group| group.0);
+
+    let is_flush = rules.counts_straights_and_flushes() && is_hand_same_symbol(hand);
+    let is_straight = get_straight_high(rules, hand).is_some();
+
+    if largest == 5 {
+        HandType::FiveOfKind
+    } else if is_straight && is_flush {
+        HandType::StraightFlush
+    } else if largest == 4 {
+        HandType::FourOfKind
+    } else if largest == 3 && second == 2 {
+        HandType::FullHouse
+    } else if is_flush {
+        HandType::Flush
+    } else if is_straight {
+        HandType::Straight
+    } else if largest == 3 {
+        HandType::ThreeOfKind
+    } else if largest == 2 && second == 2 {
+        HandType::TwoPair
+    } else if largest == 2 {
+        HandType::OnePair
+    } else {
+        HandType::HighCard
     }
+}
+
+// Counts of each rank in the hand, largest group first and higher ranks
+// first within groups of the same size.
+fn get_rank_groups(rules: RankingRules, hand: &[Card]) -> Vec<(usize, Rank)> {
+    let mut groups: Vec<(usize, Rank)> = Vec::new();
+
+    for card in hand {
+        match groups.iter_mut().find(|(_, rank)| *rank == card.rank) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, card.rank)),
+        }
+    }
+
+    groups.sort_unstable_by_key(|(count, rank)| {
+        (Reverse(*count), Reverse(get_rank_value(rules, *rank)))
+    });
 
-    high
+    groups
 }
 
-fn is_hand_same_symbol(hand: &Vec<Card>) -> bool {
-    hand[0].symbol == hand[1].symbol
-        && hand[1].symbol == hand[2].symbol
-        && hand[2].symbol == hand[3].symbol
-        && hand[3].symbol == hand[4].symbol
+// Top card of the straight made by five distinct consecutive ranks. The ace
+// plays low in A-2-3-4-5 under high rules, and below the six in A-6-7-8-9
+// under short-deck rules.
+fn get_straight_high(rules: RankingRules, hand: &[Card]) -> Option<Rank> {
+    if hand.len() != 5 || !rules.counts_straights_and_flushes() {
+        return None;
+    }
+
+    let mut values: Vec<u8> = hand.iter().map(|card| card.rank.value()).collect();
+    values.sort_unstable();
+    values.dedup();
+
+    if values.len() != 5 {
+        return None;
+    }
+
+    if values == [2, 3, 4, 5, 14] && rules == RankingRules::High {
+        Some(Rank::Five)
+    } else if values == [6, 7, 8, 9, 14] && rules == RankingRules::ShortDeck {
+        Some(Rank::Nine)
+    } else if values[4] - values[0] == 4 {
+        Rank::from_value(values[4])
+    } else {
+        None
+    }
 }
 
-fn get_hand_rank<'a>(hand_type: &HandType, hand: &'a str) -> i8 {
-    if *hand_type == HandType::FiveOfKind(hand) {
-        return 11;
-    } else if *hand_type == HandType::StraightFlush(hand) {
-        return 10;
-    } else if *hand_type == HandType::FourOfKind(hand) {
-        return 9;
-    } else if *hand_type == HandType::FullHouse(hand) {
-        return 8;
-    } else if *hand_type == HandType::Flush(hand) {
-        return 7;
-    } else if *hand_type == HandType::Straight(hand) {
-        return 6;
-    } else if *hand_type == HandType::ThreeOfKind(hand) {
-        return 5;
-    } else if *hand_type == HandType::TwoPair(hand) {
-        return 4;
-    } else if *hand_type == HandType::OnePair(hand) {
-        return 3;
+fn get_rank_value(rules: RankingRules, rank: Rank) -> u8 {
+    if rank == Rank::Ace && rules == RankingRules::AceToFive {
+        1
+    } else {
+        rank.value()
     }
+}
 
-    0
+fn is_hand_same_symbol(hand: &[Card]) -> bool {
+    hand.len() == 5 && hand.iter().all(|card| card.suit == hand[0].suit)
 }
 
-fn get_card_sum<'a>(hand: &'a str) -> i8 {
-    let cards: Vec<&str> = hand.split(" ").collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut total_sum: i8 = 0;
+    #[test]
+    fn winning_hands_accepts_repeated_cards() {
+        let hands = ["4S 4S 4S 8D 8H", "2S 3S 4S 5S 7D"];
 
-    for card in cards {
-        let card_split: Vec<&str> = card.split("").collect();
-        total_sum += get_rank_by_char(card_split[1], hand);
+        assert_eq!(winning_hands(&hands), vec!["4S 4S 4S 8D 8H"]);
     }
 
-    total_sum
+    #[test]
+    fn try_winning_hands_reports_malformed_cards() {
+        let err = try_winning_hands(&["4S 5S 7H 8D JC", "2S 3S H 5S 7D"]).unwrap_err();
+
+        assert_eq!(
+            err,
+            HandError::Malformed {
+                hand: 1,
+                token: "H".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn two_pair_compares_the_high_pair_first() {
+        let kings_up = parse_hand("KS KH 3C 3D 2S").unwrap();
+        let queens_up = parse_hand("QS QH JC JD AS").unwrap();
+
+        assert!(evaluate(&kings_up) > evaluate(&queens_up));
+        assert_eq!(
+            winning_hands(&["QS QH JC JD AS", "KS KH 3C 3D 2S"]),
+            vec!["KS KH 3C 3D 2S"]
+        );
+    }
+
+    #[test]
+    fn ranks_are_plain_digits_or_letters() {
+        assert_eq!(
+            "10H".parse::<Card>().unwrap(),
+            Card::new(Rank::Ten, Suit::Hearts)
+        );
+        assert_eq!(
+            "5h".parse::<Card>().unwrap(),
+            Card::new(Rank::Five, Suit::Hearts)
+        );
+
+        for token in ["+5H", "05H", "1H", "11H"] {
+            assert_eq!(
+                token.parse::<Card>().unwrap_err().kind(),
+                ParseCardErrorKind::InvalidRank,
+                "{:?}",
+                token
+            );
+        }
+    }
 }
@@ -0,0 +1,403 @@
+use std::cmp::Ordering;
+
+use super::{
+    fast::{evaluate_mask, CardMask},
+    range::{Combo, Range},
+    rng::SeededRng,
+    table::{Action, ActionError, ActionRecord, LegalActions, Street, Table, TableConfig},
+    Card, Rank, Suit,
+};
+
+/// What a player sees when it is their turn. Other players' hole cards stay
+/// hidden.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameState<'a> {
+    pub seat: usize,
+    pub button: usize,
+    pub hole_cards: [Card; 2],
+    pub board: &'a [Card],
+    pub street: Street,
+    pub pot: u64,
+    /// The largest bet of the current street.
+    pub current_bet: u64,
+    pub stacks: Vec<u64>,
+    pub street_bets: Vec<u64>,
+    /// Whether each seat still holds cards.
+    pub live: Vec<bool>,
+    pub legal: LegalActions,
+    pub config: &'a TableConfig,
+    /// Actions taken so far in the hand.
+    pub actions: &'a [ActionRecord],
+}
+
+impl GameState<'_> {
+    /// Checks when that is free, calls otherwise.
+    pub fn check_or_call(&self) -> Action {
+        if self.legal.can_check() {
+            Action::Check
+        } else {
+            Action::Call
+        }
+    }
+
+    /// Checks when that is free, folds otherwise.
+    pub fn check_or_fold(&self) -> Action {
+        if self.legal.can_check() {
+            Action::Check
+        } else {
+            Action::Fold
+        }
+    }
+
+    /// A bet or raise to the given total, moved within the legal amounts, or
+    /// `None` when the player may not raise.
+    pub fn bet_or_raise(&self, to: u64) -> Option<Action> {
+        let (min, max) = (self.legal.min_raise_to?, self.legal.max_raise_to?);
+        let to = to.clamp(min, max);
+
+        if self.current_bet == 0 {
+            Some(Action::Bet(to))
+        } else {
+            Some(Action::Raise(to))
+        }
+    }
+}
+
+/// A player that picks its own actions, such as a bot under evaluation.
+pub trait Player {
+    fn name(&self) -> &str;
+
+    /// Picks an action for the seat to act. An illegal action is replaced by
+    /// a check, or a fold when there is a bet to call.
+    fn act(&mut self, state: &GameState<'_>) -> Action;
+}
+
+/// Picks uniformly between folding, checking or calling and raising, and
+/// raises to a random legal amount.
+#[derive(Debug, Clone)]
+pub struct RandomPlayer {
+    rng: SeededRng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        RandomPlayer {
+            rng: SeededRng::new(seed),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn name(&self) -> &str {
+        "Random"
+    }
+
+    fn act(&mut self, state: &GameState<'_>) -> Action {
+        match self.rng.below(3) {
+            0 => state.check_or_fold(),
+            1 => state.check_or_call(),
+            _ => match (state.legal.min_raise_to, state.legal.max_raise_to) {
+                (Some(min), Some(max)) => {
+                    let to = min + self.rng.below((max - min) as usize + 1) as u64;
+                    state.bet_or_raise(to).unwrap_or(state.check_or_call())
+                }
+                _ => state.check_or_call(),
+            },
+        }
+    }
+}
+
+/// Never folds and never raises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CallingStation;
+
+impl Player for CallingStation {
+    fn name(&self) -> &str {
+        "Calling station"
+    }
+
+    fn act(&mut self, state: &GameState<'_>) -> Action {
+        state.check_or_call()
+    }
+}
+
+/// Plays few hands and bets the good ones. Before the flop it raises the
+/// opening range and folds the rest; after it, the hand strength is the
+/// share of the opponent's possible hole cards the hand currently beats.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TightAggressive {
+    pub opening: Range,
+    /// Hand strength from which it bets or raises.
+    pub value: f64,
+    /// Hand strength from which it calls.
+    pub call: f64,
+}
+
+impl Default for TightAggressive {
+    fn default() -> Self {
+        TightAggressive {
+            opening: "77+, ATs+, KTs+, QJs, AJo+, KQo"
+                .parse()
+                .expect("the opening range is valid"),
+            value: 0.85,
+            call: 0.6,
+        }
+    }
+}
+
+impl Player for TightAggressive {
+    fn name(&self) -> &str {
+        "Tight-aggressive"
+    }
+
+    fn act(&mut self, state: &GameState<'_>) -> Action {
+        let [first, second] = state.hole_cards;
+
+        if state.street == Street::Preflop {
+            if !self.opening.contains(Combo::new(first, second)) {
+                return state.check_or_fold();
+            }
+
+            let to = (state.current_bet * 3).max(state.config.big_blind * 3);
+            return state
+                .bet_or_raise(to)
+                .unwrap_or_else(|| state.check_or_call());
+        }
+
+        let strength = get_hand_strength(state.hole_cards, state.board);
+
+        if strength >= self.value {
+            let to = state.current_bet + state.pot.max(state.config.big_blind);
+            state
+                .bet_or_raise(to)
+                .unwrap_or_else(|| state.check_or_call())
+        } else if strength >= self.call {
+            state.check_or_call()
+        } else {
+            state.check_or_fold()
+        }
+    }
+}
+
+// Share of the opponent's possible hole cards the hand beats on the current
+// board, ties counting half.
+fn get_hand_strength(hole_cards: [Card; 2], board: &[Card]) -> f64 {
+    let hand = CardMask::from_cards(&hole_cards);
+    let board_mask = CardMask::from_cards(board);
+    let value = evaluate_mask(hand | board_mask);
+
+    let deck: Vec<Card> = Suit::ALL
+        .iter()
+        .flat_map(|suit| Rank::ALL.iter().map(|rank| Card::new(*rank, *suit)))
+        .filter(|card| !hand.contains(*card) && !board_mask.contains(*card))
+        .collect();
+
+    let mut won = 0.0;
+    let mut total = 0.0;
+
+    for (i, first) in deck.iter().enumerate() {
+        for second in &deck[i + 1..] {
+            let other = evaluate_mask(CardMask::from_cards(&[*first, *second]) | board_mask);
+            won += match value.cmp(&other) {
+                Ordering::Greater => 1.0,
+                Ordering::Equal => 0.5,
+                Ordering::Less => 0.0,
+            };
+            total += 1.0;
+        }
+    }
+
+    won / total
+}
+
+/// How [`play_match`] runs the hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchConfig {
+    pub table: TableConfig,
+    /// Every hand starts with all stacks reset to this amount, so hands are
+    /// independent of each other.
+    pub starting_stack: u64,
+    pub hands: usize,
+    pub seed: u64,
+}
+
+/// Chips one player won or lost over a match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerResult {
+    pub name: String,
+    pub hands: usize,
+    pub net: i64,
+    /// Average chips won per hand.
+    pub mean: f64,
+    /// Half the width of the 95% confidence interval of `mean`.
+    pub margin: f64,
+    /// Number of actions replaced because they were illegal.
+    pub illegal_actions: usize,
+}
+
+impl PlayerResult {
+    /// The 95% confidence interval of the chips won per hand.
+    pub fn interval(&self) -> (f64, f64) {
+        (self.mean - self.margin, self.mean + self.margin)
+    }
+
+    /// Big blinds won per 100 hands, the usual win rate.
+    pub fn big_blinds_per_100(&self, big_blind: u64) -> f64 {
+        self.mean * 100.0 / big_blind as f64
+    }
+}
+
+/// Seats the players in order and plays `config.hands` hands of Hold'em
+/// between them, moving the button one seat each hand. Decks are shuffled
+/// from `config.seed`, so the same players and seed give the same results.
+/// Showdowns are settled by the table.
+pub fn play_match(
+    players: &mut [Box<dyn Player>],
+    config: &MatchConfig,
+) -> Result<Vec<PlayerResult>, ActionError> {
+    if players.len() < 2 {
+        return Err(ActionError::NotEnoughPlayers);
+    }
+
+    let stacks = vec![config.starting_stack; players.len()];
+    let mut rng = SeededRng::new(config.seed);
+    let mut results: Vec<Vec<i64>> = vec![Vec::with_capacity(config.hands); players.len()];
+    let mut illegal_actions = vec![0; players.len()];
+
+    for hand in 0..config.hands {
+        let mut table = Table::new(config.table, &stacks, hand % players.len());
+        table.start_hand(rng.next_u64())?;
+
+        while let (Some(seat), Some(legal)) = (table.to_act(), table.legal_actions()) {
+            let hole_cards = table.seats()[seat]
+                .hole_cards()
+                .ok_or(ActionError::NoHandInProgress)?;
+            let (action, fallback) = {
+                let state = GameState {
+                    seat,
+                    button: table.button(),
+                    hole_cards,
+                    board: table.board(),
+                    street: table.street(),
+                    pot: table.pot(),
+                    current_bet: table
+                        .seats()
+                        .iter()
+                        .map(|seat| seat.street_bet())
+                        .max()
+                        .unwrap_or(0),
+                    stacks: table.seats().iter().map(|seat| seat.stack()).collect(),
+                    street_bets: table.seats().iter().map(|seat| seat.street_bet()).collect(),
+                    live: table.seats().iter().map(|seat| seat.is_live()).collect(),
+                    legal,
+                    config: table.config(),
+                    actions: table.actions(),
+                };
+
+                (players[seat].act(&state), state.check_or_fold())
+            };
+
+            if table.act(action).is_err() {
+                illegal_actions[seat] += 1;
+                table.act(fallback)?;
+            }
+        }
+
+        for (seat, result) in table.seats().iter().zip(results.iter_mut()) {
+            result.push(seat.stack() as i64 - config.starting_stack as i64);
+        }
+    }
+
+    Ok(players
+        .iter()
+        .zip(results)
+        .zip(illegal_actions)
+        .map(|((player, results), illegal_actions)| {
+            let hands = results.len();
+            let net: i64 = results.iter().sum();
+            let mean = net as f64 / hands.max(1) as f64;
+
+            // Normal approximation of the mean over independent hands.
+            let variance = results
+                .iter()
+                .map(|result| (*result as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (hands.max(2) - 1) as f64;
+            let margin = 1.96 * (variance / hands.max(1) as f64).sqrt();
+
+            PlayerResult {
+                name: player.name().to_string(),
+                hands,
+                net,
+                mean,
+                margin,
+                illegal_actions,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::table::Limit;
+
+    fn config(limit: Limit, starting_stack: u64) -> MatchConfig {
+        MatchConfig {
+            table: TableConfig {
+                small_blind: 10,
+                big_blind: 20,
+                ante: 5,
+                limit,
+            },
+            starting_stack,
+            hands: 200,
+            seed: 11,
+        }
+    }
+
+    fn players() -> Vec<Box<dyn Player>> {
+        vec![
+            Box::new(RandomPlayer::new(1)),
+            Box::new(CallingStation),
+            Box::new(TightAggressive::default()),
+            Box::new(RandomPlayer::new(2)),
+        ]
+    }
+
+    #[test]
+    fn matches_repeat_and_keep_every_chip() {
+        let config = config(Limit::NoLimit, 2000);
+        let results = play_match(&mut players(), &config).unwrap();
+
+        assert_eq!(results, play_match(&mut players(), &config).unwrap());
+        assert_eq!(results.iter().map(|result| result.net).sum::<i64>(), 0);
+        assert!(results.iter().all(|result| result.hands == 200));
+
+        let reseeded = MatchConfig { seed: 12, ..config };
+        assert_ne!(results, play_match(&mut players(), &reseeded).unwrap());
+    }
+
+    #[test]
+    fn baseline_players_only_pick_legal_actions() {
+        // Short stacks put players all-in for less than a full bet or raise.
+        for config in [
+            config(Limit::NoLimit, 2000),
+            config(Limit::NoLimit, 150),
+            config(Limit::PotLimit, 600),
+        ] {
+            for result in play_match(&mut players(), &config).unwrap() {
+                assert_eq!(result.illegal_actions, 0, "{}", result.name);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_need_two_players() {
+        let mut players: Vec<Box<dyn Player>> = vec![Box::new(CallingStation)];
+        assert_eq!(
+            play_match(&mut players, &config(Limit::NoLimit, 2000)),
+            Err(ActionError::NotEnoughPlayers)
+        );
+    }
+}
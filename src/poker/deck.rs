@@ -0,0 +1,417 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use super::{rng::SeededRng, Card, Rank, Suit};
+
+/// A card dealt from a [`Deck`], which may be a joker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DeckCard {
+    Card(Card),
+    Joker,
+}
+
+impl DeckCard {
+    /// The regular card, or `None` for a joker.
+    pub fn card(self) -> Option<Card> {
+        match self {
+            DeckCard::Card(card) => Some(card),
+            DeckCard::Joker => None,
+        }
+    }
+}
+
+impl From<Card> for DeckCard {
+    fn from(card: Card) -> Self {
+        DeckCard::Card(card)
+    }
+}
+
+impl fmt::Display for DeckCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckCard::Card(card) => write!(f, "{}", card),
+            DeckCard::Joker => write!(f, "JK"),
+        }
+    }
+}
+
+/// Parses a card the way [`Card`] does, with `JK` for a joker.
+impl FromStr for DeckCard {
+    type Err = DeckError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "JK" {
+            return Ok(DeckCard::Joker);
+        }
+
+        match s.parse::<Card>() {
+            Ok(card) => Ok(DeckCard::Card(card)),
+            Err(err) => Err(DeckError::InvalidState(err.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DeckKind {
+    /// The regular 52 card deck.
+    #[default]
+    Standard,
+    /// The 36 card deck used in short-deck (6+) poker, with the deuces
+    /// through fives removed.
+    ShortDeck,
+}
+
+impl DeckKind {
+    fn ranks(self) -> &'static [Rank] {
+        match self {
+            DeckKind::Standard => &Rank::ALL,
+            DeckKind::ShortDeck => &Rank::ALL[4..],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckError {
+    NotEnoughCards { requested: usize, remaining: usize },
+    CardNotInDeck(Card),
+    InvalidState(String),
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckError::NotEnoughCards {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "cannot deal {} cards, only {} remain",
+                requested, remaining
+            ),
+            DeckError::CardNotInDeck(card) => write!(f, "the card {} is not in the deck", card),
+            DeckError::InvalidState(reason) => write!(f, "invalid deck state: {}", reason),
+        }
+    }
+}
+
+impl Error for DeckError {}
+
+/// A deck of cards dealt from the top. Shuffles are driven by a seed, so the
+/// same seed always deals the same cards.
+///
+/// A deck serializes to a single line with [`fmt::Display`] and reads back
+/// with [`FromStr`], which restores the exact remaining order along with the
+/// cards already dealt and burned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deck {
+    seed: Option<u64>,
+    // Remaining cards, the top of the deck being the last one.
+    cards: Vec<DeckCard>,
+    dealt: Vec<DeckCard>,
+    burned: Vec<DeckCard>,
+}
+
+impl Deck {
+    /// An unshuffled deck of the given kind with `jokers` jokers added.
+    pub fn new(kind: DeckKind, jokers: usize) -> Self {
+        let mut cards: Vec<DeckCard> = Vec::new();
+
+        for suit in Suit::ALL {
+            for rank in kind.ranks() {
+                cards.push(DeckCard::Card(Card::new(*rank, suit)));
+            }
+        }
+        cards.extend(std::iter::repeat_n(DeckCard::Joker, jokers));
+        cards.reverse();
+
+        Deck {
+            seed: None,
+            cards,
+            dealt: Vec::new(),
+            burned: Vec::new(),
+        }
+    }
+
+    /// A standard 52 card deck shuffled with the given seed.
+    pub fn shuffled(seed: u64) -> Self {
+        let mut deck = Deck::new(DeckKind::Standard, 0);
+        deck.shuffle(seed);
+        deck
+    }
+
+    /// Shuffles the remaining cards.
+    pub fn shuffle(&mut self, seed: u64) {
+        SeededRng::new(seed).shuffle(&mut self.cards);
+        self.seed = Some(seed);
+    }
+
+    /// Puts cards back under the remaining ones and shuffles them all with
+    /// the given seed, e.g. the discards once the stub runs short.
+    pub fn reshuffle(&mut self, cards: &[Card], seed: u64) {
+        self.cards
+            .splice(0..0, cards.iter().map(|card| DeckCard::Card(*card)));
+        self.shuffle(seed);
+    }
+
+    /// Seed of the last shuffle, if the deck was shuffled.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Remaining cards, from the top of the deck down.
+    pub fn remaining(&self) -> impl Iterator<Item = &DeckCard> {
+        self.cards.iter().rev()
+    }
+
+    /// Every card dealt so far, in dealing order.
+    pub fn dealt(&self) -> &[DeckCard] {
+        &self.dealt
+    }
+
+    pub fn burned(&self) -> &[DeckCard] {
+        &self.burned
+    }
+
+    /// Deals `n` cards from the top of the deck.
+    pub fn deal(&mut self, n: usize) -> Result<Vec<DeckCard>, DeckError> {
+        if n > self.cards.len() {
+            return Err(DeckError::NotEnoughCards {
+                requested: n,
+                remaining: self.cards.len(),
+            });
+        }
+
+        let cards: Vec<DeckCard> = self.cards.split_off(self.cards.len() - n);
+        let cards: Vec<DeckCard> = cards.into_iter().rev().collect();
+        self.dealt.extend_from_slice(&cards);
+
+        Ok(cards)
+    }
+
+    /// Deals `n` cards, failing if any of them is a joker.
+    pub fn deal_cards(&mut self, n: usize) -> Result<Vec<Card>, DeckError> {
+        if self
+            .cards
+            .iter()
+            .rev()
+            .take(n)
+            .any(|card| card.card().is_none())
+        {
+            return Err(DeckError::InvalidState(
+                "a joker is among the cards to deal".to_string(),
+            ));
+        }
+
+        Ok(self
+            .deal(n)?
+            .into_iter()
+            .filter_map(DeckCard::card)
+            .collect())
+    }
+
+    /// Discards the top card face down.
+    pub fn burn(&mut self) -> Result<DeckCard, DeckError> {
+        match self.cards.pop() {
+            Some(card) => {
+                self.burned.push(card);
+                Ok(card)
+            }
+            None => Err(DeckError::NotEnoughCards {
+                requested: 1,
+                remaining: 0,
+            }),
+        }
+    }
+
+    /// Takes known cards out of the deck, e.g. the hole cards of a hand being
+    /// analysed. Nothing is removed if any of the cards is missing.
+    pub fn remove(&mut self, cards: &[Card]) -> Result<(), DeckError> {
+        if let Some(card) = cards
+            .iter()
+            .find(|card| !self.cards.contains(&DeckCard::Card(**card)))
+        {
+            return Err(DeckError::CardNotInDeck(*card));
+        }
+
+        self.cards
+            .retain(|card| card.card().is_none_or(|card| !cards.contains(&card)));
+
+        Ok(())
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new(DeckKind::Standard, 0)
+    }
+}
+
+/// Writes the deck as `seed=42;deck=AS KD ...;dealt=...;burned=...`, with the
+/// remaining cards listed from the top down.
+impl fmt::Display for Deck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |cards: &mut dyn Iterator<Item = &DeckCard>| {
+            cards
+                .map(|card| card.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+
+        let seed = match self.seed {
+            Some(seed) => seed.to_string(),
+            None => "none".to_string(),
+        };
+
+        write!(
+            f,
+            "seed={};deck={};dealt={};burned={}",
+            seed,
+            join(&mut self.cards.iter().rev()),
+            join(&mut self.dealt.iter()),
+            join(&mut self.burned.iter())
+        )
+    }
+}
+
+impl FromStr for Deck {
+    type Err = DeckError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().split(';');
+        let mut next_field = |name: &str| -> Result<&str, DeckError> {
+            match fields.next().and_then(|field| field.strip_prefix(name)) {
+                Some(value) => Ok(value),
+                None => Err(DeckError::InvalidState(format!("missing {}", name))),
+            }
+        };
+
+        let seed = match next_field("seed=")? {
+            "none" => None,
+            seed => match seed.parse::<u64>() {
+                Ok(seed) => Some(seed),
+                Err(_) => return Err(DeckError::InvalidState(format!("bad seed {:?}", seed))),
+            },
+        };
+
+        let mut cards = parse_deck_cards(next_field("deck=")?)?;
+        cards.reverse();
+        let dealt = parse_deck_cards(next_field("dealt=")?)?;
+        let burned = parse_deck_cards(next_field("burned=")?)?;
+
+        Ok(Deck {
+            seed,
+            cards,
+            dealt,
+            burned,
+        })
+    }
+}
+
+fn parse_deck_cards(cards: &str) -> Result<Vec<DeckCard>, DeckError> {
+    cards.split_whitespace().map(str::parse).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::parse_hand;
+
+    #[test]
+    fn decks_read_back_what_they_write() {
+        let mut deck = Deck::new(DeckKind::ShortDeck, 2);
+        deck.shuffle(42);
+        deck.deal(5).unwrap();
+        deck.burn().unwrap();
+        deck.deal(3).unwrap();
+
+        let text = deck.to_string();
+        assert!(text.starts_with("seed=42;deck="));
+        let read: Deck = text.parse().unwrap();
+        assert_eq!(read, deck);
+
+        // The copy deals the same cards from here on.
+        let mut original = deck.clone();
+        let mut copy = read;
+        assert_eq!(copy.deal(20).unwrap(), original.deal(20).unwrap());
+
+        let fresh = Deck::default();
+        assert_eq!(fresh.to_string().parse::<Deck>().unwrap(), fresh);
+        assert!(fresh.to_string().starts_with("seed=none;deck=2C 3C 4C"));
+    }
+
+    #[test]
+    fn broken_states_are_errors() {
+        for text in [
+            "seed=x;deck=AS;dealt=;burned=",
+            "seed=1;deck=AS ZZ;dealt=;burned=",
+            "seed=1;deck=AS;burned=",
+            "deck=AS;dealt=;burned=",
+        ] {
+            assert!(
+                matches!(text.parse::<Deck>(), Err(DeckError::InvalidState(_))),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn burned_cards_are_not_dealt() {
+        let mut deck = Deck::shuffled(7);
+        let top = *deck.remaining().next().unwrap();
+
+        assert_eq!(deck.burn(), Ok(top));
+        assert_eq!(deck.burned(), [top]);
+        assert_eq!(deck.len(), 51);
+        assert!(deck.dealt().is_empty());
+        assert!(!deck.deal(51).unwrap().contains(&top));
+
+        assert_eq!(
+            deck.burn(),
+            Err(DeckError::NotEnoughCards {
+                requested: 1,
+                remaining: 0
+            })
+        );
+    }
+
+    #[test]
+    fn removing_cards_takes_all_or_nothing() {
+        let mut deck = Deck::shuffled(3);
+        let hole_cards = parse_hand("AS KD").unwrap();
+
+        deck.remove(&hole_cards).unwrap();
+        assert_eq!(deck.len(), 50);
+        assert!(deck
+            .remaining()
+            .all(|card| card.card().is_some_and(|card| !hole_cards.contains(&card))));
+
+        let before = deck.clone();
+        let cards = parse_hand("QH AS").unwrap();
+        assert_eq!(deck.remove(&cards), Err(DeckError::CardNotInDeck(cards[1])));
+        assert_eq!(deck, before);
+    }
+
+    #[test]
+    fn jokers_are_not_dealt_as_cards() {
+        // Unshuffled, the jokers lie at the bottom.
+        let mut deck = Deck::new(DeckKind::Standard, 1);
+        assert_eq!(deck.len(), 53);
+        assert_eq!(deck.deal(53).unwrap().last(), Some(&DeckCard::Joker));
+
+        let mut deck = Deck::new(DeckKind::Standard, 1);
+        deck.deal_cards(51).unwrap();
+        assert!(matches!(
+            deck.deal_cards(2),
+            Err(DeckError::InvalidState(_))
+        ));
+        assert_eq!(deck.len(), 2);
+    }
+}
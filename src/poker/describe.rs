@@ -0,0 +1,326 @@
+use std::fmt;
+
+use super::{HandRank, HandType, Rank, RankingRules};
+
+/// Wording used to describe hands, so clients can supply other languages.
+/// Each category gets the ranks that matter for it; kickers are `None` when
+/// the hand has none, e.g. four of a kind made with a wild card.
+pub trait HandWording {
+    /// Name of the category, e.g. `Full house`.
+    fn hand_type(&self, hand_type: HandType) -> String;
+    fn high_card(&self, high: Rank, kicker: Option<Rank>) -> String;
+    fn one_pair(&self, pair: Rank, kicker: Option<Rank>) -> String;
+    fn two_pair(&self, high: Rank, low: Rank, kicker: Option<Rank>) -> String;
+    fn three_of_kind(&self, trips: Rank, kicker: Option<Rank>) -> String;
+    fn straight(&self, high: Rank) -> String;
+    fn flush(&self, high: Rank) -> String;
+    fn full_house(&self, trips: Rank, pair: Rank) -> String;
+    fn four_of_kind(&self, quads: Rank, kicker: Option<Rank>) -> String;
+    fn straight_flush(&self, high: Rank) -> String;
+    fn five_of_kind(&self, rank: Rank) -> String;
+}
+
+/// English descriptions such as `Full house, Kings full of Fours` or
+/// `Two pair, Queens and Jacks with an Eight kicker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct English;
+
+impl English {
+    fn plural(rank: Rank) -> String {
+        match rank {
+            Rank::Six => "Sixes".to_string(),
+            rank => format!("{}s", rank.name()),
+        }
+    }
+
+    // " with an Eight kicker", or nothing without a kicker.
+    fn kicker(kicker: Option<Rank>) -> String {
+        match kicker {
+            Some(kicker) => {
+                let article = match kicker {
+                    Rank::Eight | Rank::Ace => "an",
+                    _ => "a",
+                };
+                format!(" with {} {} kicker", article, kicker.name())
+            }
+            None => String::new(),
+        }
+    }
+}
+
+impl HandWording for English {
+    fn hand_type(&self, hand_type: HandType) -> String {
+        match hand_type {
+            HandType::HighCard => "High card",
+            HandType::OnePair => "One pair",
+            HandType::TwoPair => "Two pair",
+            HandType::ThreeOfKind => "Three of a kind",
+            HandType::Straight => "Straight",
+            HandType::Flush => "Flush",
+            HandType::FullHouse => "Full house",
+            HandType::FourOfKind => "Four of a kind",
+            HandType::StraightFlush => "Straight flush",
+            HandType::FiveOfKind => "Five of a kind",
+        }
+        .to_string()
+    }
+
+    fn high_card(&self, high: Rank, kicker: Option<Rank>) -> String {
+        format!("High card, {}{}", high.name(), English::kicker(kicker))
+    }
+
+    fn one_pair(&self, pair: Rank, kicker: Option<Rank>) -> String {
+        format!(
+            "One pair, {}{}",
+            English::plural(pair),
+            English::kicker(kicker)
+        )
+    }
+
+    fn two_pair(&self, high: Rank, low: Rank, kicker: Option<Rank>) -> String {
+        format!(
+            "Two pair, {} and {}{}",
+            English::plural(high),
+            English::plural(low),
+            English::kicker(kicker)
+        )
+    }
+
+    fn three_of_kind(&self, trips: Rank, kicker: Option<Rank>) -> String {
+        format!(
+            "Three of a kind, {}{}",
+            English::plural(trips),
+            English::kicker(kicker)
+        )
+    }
+
+    fn straight(&self, high: Rank) -> String {
+        format!("Straight, {} high", high.name())
+    }
+
+    fn flush(&self, high: Rank) -> String {
+        format!("Flush, {} high", high.name())
+    }
+
+    fn full_house(&self, trips: Rank, pair: Rank) -> String {
+        format!(
+            "Full house, {} full of {}",
+            English::plural(trips),
+            English::plural(pair)
+        )
+    }
+
+    fn four_of_kind(&self, quads: Rank, kicker: Option<Rank>) -> String {
+        format!(
+            "Four of a kind, {}{}",
+            English::plural(quads),
+            English::kicker(kicker)
+        )
+    }
+
+    fn straight_flush(&self, high: Rank) -> String {
+        match high {
+            Rank::Ace => "Royal flush".to_string(),
+            high => format!("Straight flush, {} high", high.name()),
+        }
+    }
+
+    fn five_of_kind(&self, rank: Rank) -> String {
+        format!("Five of a kind, {}", English::plural(rank))
+    }
+}
+
+impl HandRank {
+    /// Describes the hand in English, e.g. `Full house, Kings full of Fours`.
+    pub fn describe(&self) -> String {
+        self.describe_with(&English)
+    }
+
+    pub fn describe_with(&self, wording: &dyn HandWording) -> String {
+        let rank = |i: usize| self.ranks.get(i).copied();
+        let first = match rank(0) {
+            Some(first) => first,
+            None => return wording.hand_type(self.hand_type),
+        };
+
+        match self.hand_type {
+            HandType::HighCard => wording.high_card(first, rank(1)),
+            HandType::OnePair => wording.one_pair(first, rank(1)),
+            HandType::TwoPair => match rank(1) {
+                Some(second) => wording.two_pair(first, second, rank(2)),
+                None => wording.one_pair(first, None),
+            },
+            HandType::ThreeOfKind => wording.three_of_kind(first, rank(1)),
+            HandType::Straight => wording.straight(first),
+            HandType::Flush => wording.flush(first),
+            HandType::FullHouse => wording.full_house(first, rank(1).unwrap_or(first)),
+            HandType::FourOfKind => wording.four_of_kind(first, rank(1)),
+            HandType::StraightFlush => wording.straight_flush(first),
+            HandType::FiveOfKind => wording.five_of_kind(first),
+        }
+    }
+
+    /// The ranks of the five cards, grouped and highest first, e.g. `KKK44`
+    /// for a full house or `5432A` for the wheel. The rules tell which card
+    /// completes a straight at the bottom, e.g. `9876A` in short-deck.
+    pub fn code(&self, rules: RankingRules) -> String {
+        // The ace plays below the lowest rank of the deck.
+        let below_lowest = match rules {
+            RankingRules::ShortDeck => Rank::Six.value() - 1,
+            _ => Rank::Two.value() - 1,
+        };
+
+        let counts: &[usize] = match self.hand_type {
+            HandType::OnePair => &[2, 1, 1, 1],
+            HandType::TwoPair => &[2, 2, 1],
+            HandType::ThreeOfKind => &[3, 1, 1],
+            HandType::FullHouse => &[3, 2],
+            HandType::FourOfKind => &[4, 1],
+            HandType::FiveOfKind => &[5],
+            _ => &[1, 1, 1, 1, 1],
+        };
+
+        let ranks: Vec<Rank> = match (self.hand_type, self.ranks.first()) {
+            (HandType::Straight | HandType::StraightFlush, Some(high)) => (0..5)
+                .map(|step| match high.value() - step {
+                    value if value == below_lowest => Rank::Ace,
+                    value => Rank::from_value(value).unwrap_or(Rank::Ace),
+                })
+                .collect(),
+            _ => self
+                .ranks
+                .iter()
+                .zip(counts)
+                .flat_map(|(rank, count)| std::iter::repeat_n(*rank, *count))
+                .collect(),
+        };
+
+        ranks.iter().map(|rank| rank.short_symbol()).collect()
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", English.hand_type(*self))
+    }
+}
+
+/// Writes the English description, see [`HandRank::describe`].
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::parse_hand;
+
+    fn code(rules: RankingRules, hand: &str) -> String {
+        rules.evaluate(&parse_hand(hand).unwrap()).code(rules)
+    }
+
+    #[test]
+    fn wheels_end_with_the_ace() {
+        assert_eq!(code(RankingRules::High, "AS 2D 3H 4C 5S"), "5432A");
+        assert_eq!(code(RankingRules::ShortDeck, "AS 6D 7H 8C 9S"), "9876A");
+        assert_eq!(code(RankingRules::High, "5S 6D 7H 8C 9S"), "98765");
+    }
+
+    #[test]
+    fn groups_come_first() {
+        assert_eq!(code(RankingRules::High, "4S KD 4H KC KS"), "KKK44");
+        assert_eq!(code(RankingRules::High, "2S QD 9H QC 3S"), "QQ932");
+    }
+
+    fn describe(hand: &str) -> String {
+        RankingRules::High
+            .evaluate(&parse_hand(hand).unwrap())
+            .describe()
+    }
+
+    #[test]
+    fn describes_hands_in_english() {
+        assert_eq!(
+            describe("4S KD 4H KC KS"),
+            "Full house, Kings full of Fours"
+        );
+        assert_eq!(
+            describe("QS QD JH JC 8S"),
+            "Two pair, Queens and Jacks with an Eight kicker"
+        );
+        assert_eq!(
+            describe("6S 6D 6H 9C 2S"),
+            "Three of a kind, Sixes with a Nine kicker"
+        );
+        assert_eq!(
+            describe("TS TD AH 9C 2S"),
+            "One pair, Tens with an Ace kicker"
+        );
+        assert_eq!(describe("AS KS QS JS TS"), "Royal flush");
+        assert_eq!(describe("9S KS QS JS TS"), "Straight flush, King high");
+    }
+
+    // Card codes only, e.g. `FH K/4`.
+    struct Codes;
+
+    impl HandWording for Codes {
+        fn hand_type(&self, hand_type: HandType) -> String {
+            format!("{:?}", hand_type)
+        }
+
+        fn high_card(&self, high: Rank, _: Option<Rank>) -> String {
+            format!("HC {}", high.short_symbol())
+        }
+
+        fn one_pair(&self, pair: Rank, _: Option<Rank>) -> String {
+            format!("1P {}", pair.short_symbol())
+        }
+
+        fn two_pair(&self, high: Rank, low: Rank, _: Option<Rank>) -> String {
+            format!("2P {}/{}", high.short_symbol(), low.short_symbol())
+        }
+
+        fn three_of_kind(&self, trips: Rank, _: Option<Rank>) -> String {
+            format!("3K {}", trips.short_symbol())
+        }
+
+        fn straight(&self, high: Rank) -> String {
+            format!("ST {}", high.short_symbol())
+        }
+
+        fn flush(&self, high: Rank) -> String {
+            format!("FL {}", high.short_symbol())
+        }
+
+        fn full_house(&self, trips: Rank, pair: Rank) -> String {
+            format!("FH {}/{}", trips.short_symbol(), pair.short_symbol())
+        }
+
+        fn four_of_kind(&self, quads: Rank, _: Option<Rank>) -> String {
+            format!("4K {}", quads.short_symbol())
+        }
+
+        fn straight_flush(&self, high: Rank) -> String {
+            format!("SF {}", high.short_symbol())
+        }
+
+        fn five_of_kind(&self, rank: Rank) -> String {
+            format!("5K {}", rank.short_symbol())
+        }
+    }
+
+    #[test]
+    fn other_wordings_can_be_supplied() {
+        let describe_with = |hand: &str| {
+            RankingRules::High
+                .evaluate(&parse_hand(hand).unwrap())
+                .describe_with(&Codes)
+        };
+
+        assert_eq!(describe_with("4S KD 4H KC KS"), "FH K/4");
+        assert_eq!(describe_with("QS QD JH JC 8S"), "2P Q/J");
+        assert_eq!(describe_with("AS KS QS JS TS"), "SF A");
+    }
+}
@@ -0,0 +1,400 @@
+use super::{
+    deck::{Deck, DeckError},
+    evaluate,
+    limit::{LimitBetting, LimitConfig},
+    pots::Pot,
+    rng::SeededRng,
+    seat::Seat,
+    table::{Action, ActionError, LegalActions},
+    Card, HandRank,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DrawPhase {
+    FirstBetting,
+    Draw,
+    SecondBetting,
+    Showdown,
+}
+
+/// Five card draw with fixed-limit betting: antes, a betting round at the
+/// small bet, one draw, a second round at the big bet and the showdown.
+/// Betting and drawing both start left of the button. When the stub runs
+/// short during the draw, its last cards are dealt and the discards are
+/// shuffled into a new stub.
+#[derive(Debug, Clone)]
+pub struct DrawGame {
+    config: LimitConfig,
+    betting: LimitBetting,
+    button: usize,
+    deck: Deck,
+    hands: Vec<Vec<Card>>,
+    cards_drawn: Vec<Option<usize>>,
+    phase: DrawPhase,
+    // Seats still to draw, the next one last.
+    draw_queue: Vec<usize>,
+    discards: Vec<Card>,
+    in_progress: bool,
+}
+
+impl DrawGame {
+    pub fn new(config: LimitConfig, stacks: &[u64], button: usize) -> Self {
+        DrawGame {
+            config,
+            betting: LimitBetting::new(stacks, config.max_bets),
+            button,
+            deck: Deck::default(),
+            hands: vec![Vec::new(); stacks.len()],
+            cards_drawn: vec![None; stacks.len()],
+            phase: DrawPhase::FirstBetting,
+            draw_queue: Vec::new(),
+            discards: Vec::new(),
+            in_progress: false,
+        }
+    }
+
+    pub fn config(&self) -> &LimitConfig {
+        &self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        self.betting.seats()
+    }
+
+    pub fn button(&self) -> usize {
+        self.button
+    }
+
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    pub fn phase(&self) -> DrawPhase {
+        self.phase
+    }
+
+    pub fn hand(&self, seat: usize) -> &[Card] {
+        &self.hands[seat]
+    }
+
+    /// Number of cards the seat drew, once it has drawn.
+    pub fn cards_drawn(&self, seat: usize) -> Option<usize> {
+        self.cards_drawn[seat]
+    }
+
+    /// The seat to bet, or to draw during the draw.
+    pub fn to_act(&self) -> Option<usize> {
+        match self.phase {
+            DrawPhase::Draw => self.draw_queue.last().copied(),
+            _ => self.betting.to_act(),
+        }
+    }
+
+    pub fn is_hand_in_progress(&self) -> bool {
+        self.in_progress
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.betting.pot()
+    }
+
+    pub fn pots(&self) -> &[Pot] {
+        self.betting.pots()
+    }
+
+    pub fn payouts(&self) -> &[u64] {
+        self.betting.payouts()
+    }
+
+    /// Shuffles a new deck with the given seed, takes the antes and deals
+    /// five cards to every seat with chips. More than ten such seats is an
+    /// error, and leaves the game as it was.
+    pub fn start_hand(&mut self, seed: u64) -> Result<(), ActionError> {
+        if self.in_progress {
+            return Err(ActionError::HandInProgress);
+        }
+        let players = self.seats().iter().filter(|seat| seat.stack() > 0).count();
+        if players < 2 {
+            return Err(ActionError::NotEnoughPlayers);
+        }
+
+        // Every seat dealt in needs five cards from a single deck.
+        let deck = Deck::shuffled(seed);
+        if players * 5 > deck.len() {
+            return Err(DeckError::NotEnoughCards {
+                requested: players * 5,
+                remaining: deck.len(),
+            }
+            .into());
+        }
+
+        self.betting.start_hand();
+        if !self.seats()[self.button].is_live() {
+            self.button = self.seats_from(self.button + 1)[0];
+        }
+        let order = self.seats_from(self.button + 1);
+
+        self.deck = deck;
+        self.hands = vec![Vec::new(); self.hands.len()];
+        self.cards_drawn = vec![None; self.hands.len()];
+        self.draw_queue.clear();
+        self.discards.clear();
+        self.phase = DrawPhase::FirstBetting;
+        self.in_progress = true;
+
+        for seat in &order {
+            self.betting.post_ante(*seat, self.config.ante);
+        }
+
+        // One card at a time, starting left of the button.
+        for _ in 0..5 {
+            for seat in &order {
+                let card = self.deck.deal_cards(1)?;
+                self.hands[*seat].extend(card);
+            }
+        }
+
+        self.betting
+            .start_round(self.config.small_bet, self.button + 1);
+        self.advance()
+    }
+
+    /// What the player to act may do, or `None` outside the betting rounds.
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        match self.phase {
+            DrawPhase::FirstBetting | DrawPhase::SecondBetting => self.betting.legal_actions(),
+            _ => None,
+        }
+    }
+
+    /// Applies a betting action for the player to act.
+    pub fn act(&mut self, action: Action) -> Result<(), ActionError> {
+        if !self.in_progress {
+            return Err(ActionError::NoHandInProgress);
+        }
+        if self.legal_actions().is_none() {
+            return Err(ActionError::WrongPhase);
+        }
+
+        self.betting.act(action)?;
+        self.advance()
+    }
+
+    /// Discards the given cards of the player to draw and replaces them from
+    /// the deck, returning the new cards. Standing pat is drawing nothing.
+    pub fn draw(&mut self, discards: &[Card]) -> Result<Vec<Card>, ActionError> {
+        let seat = match (self.phase, self.draw_queue.last()) {
+            (DrawPhase::Draw, Some(seat)) => *seat,
+            _ => return Err(ActionError::WrongPhase),
+        };
+
+        for (i, card) in discards.iter().enumerate() {
+            if !self.hands[seat].contains(card) || discards[..i].contains(card) {
+                return Err(ActionError::CardNotHeld(*card));
+            }
+        }
+
+        let mut drawn: Vec<Card> = Vec::new();
+        if self.deck.len() < discards.len() {
+            let available = self.deck.len() + self.discards.len();
+            if available < discards.len() {
+                return Err(DeckError::NotEnoughCards {
+                    requested: discards.len(),
+                    remaining: available,
+                }
+                .into());
+            }
+
+            // The player's own discards stay out of the new stub.
+            drawn = self.deck.deal_cards(self.deck.len())?;
+            let seed = self
+                .deck
+                .seed()
+                .map_or(0, |seed| SeededRng::new(seed).next_u64());
+            self.deck.reshuffle(&self.discards, seed);
+            self.discards.clear();
+        }
+        drawn.extend(self.deck.deal_cards(discards.len() - drawn.len())?);
+
+        self.discards.extend_from_slice(discards);
+        self.hands[seat].retain(|card| !discards.contains(card));
+        self.hands[seat].extend_from_slice(&drawn);
+        self.cards_drawn[seat] = Some(drawn.len());
+        self.draw_queue.pop();
+
+        self.advance()?;
+        Ok(drawn)
+    }
+
+    // Moves the hand on until a player has to act or the hand is over.
+    fn advance(&mut self) -> Result<(), ActionError> {
+        loop {
+            if self.betting.live_count() == 1 {
+                self.betting.return_uncalled_bet();
+                self.finish_hand(&[]);
+                return Ok(());
+            }
+
+            match self.phase {
+                DrawPhase::FirstBetting | DrawPhase::SecondBetting
+                    if self.betting.to_act().is_some() =>
+                {
+                    return Ok(())
+                }
+                DrawPhase::FirstBetting => {
+                    self.betting.return_uncalled_bet();
+                    self.phase = DrawPhase::Draw;
+                    self.draw_queue = self
+                        .seats_from(self.button + 1)
+                        .into_iter()
+                        .filter(|seat| self.seats()[*seat].is_live())
+                        .rev()
+                        .collect();
+                }
+                DrawPhase::Draw if !self.draw_queue.is_empty() => return Ok(()),
+                DrawPhase::Draw => {
+                    self.phase = DrawPhase::SecondBetting;
+                    self.betting
+                        .start_round(self.config.big_bet, self.button + 1);
+                }
+                DrawPhase::SecondBetting => {
+                    self.betting.return_uncalled_bet();
+                    self.showdown();
+                    return Ok(());
+                }
+                DrawPhase::Showdown => return Ok(()),
+            }
+        }
+    }
+
+    fn showdown(&mut self) {
+        self.phase = DrawPhase::Showdown;
+
+        let hand_ranks: Vec<Option<HandRank>> = self
+            .seats()
+            .iter()
+            .zip(&self.hands)
+            .map(|(seat, hand)| seat.is_live().then(|| evaluate(hand)))
+            .collect();
+
+        self.finish_hand(&hand_ranks);
+    }
+
+    fn finish_hand(&mut self, hand_ranks: &[Option<HandRank>]) {
+        self.betting.finish_hand(hand_ranks, self.button);
+        self.in_progress = false;
+        self.button = self.betting.next_with_chips(self.button);
+    }
+
+    // Seats with chips or cards, from `from` on going left.
+    fn seats_from(&self, from: usize) -> Vec<usize> {
+        let seats = self.seats();
+
+        (0..seats.len())
+            .map(|offset| (from + offset) % seats.len())
+            .filter(|seat| seats[*seat].stack() > 0 || seats[*seat].is_live())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn play_to_showdown(game: &mut DrawGame, cards: usize) {
+        while game.is_hand_in_progress() {
+            if game.phase() == DrawPhase::Draw {
+                let seat = game.to_act().expect("a seat is to draw");
+                let discards = game.hand(seat)[..cards].to_vec();
+                assert_eq!(game.draw(&discards).unwrap().len(), cards);
+            } else {
+                let legal = game.legal_actions().expect("a seat is to act");
+                let action = if legal.can_check() {
+                    Action::Check
+                } else {
+                    Action::Call
+                };
+                game.act(action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn discards_are_reshuffled_when_the_stub_runs_short() {
+        let config = LimitConfig {
+            ante: 1,
+            bring_in: 0,
+            small_bet: 2,
+            big_bet: 4,
+            max_bets: 4,
+        };
+        let mut game = DrawGame::new(config, &[100; 9], 0);
+        game.start_hand(7).unwrap();
+
+        // Nine hands of five leave a stub of seven cards, which runs out on
+        // the third player to draw three.
+        play_to_showdown(&mut game, 3);
+
+        let held: HashSet<Card> = (0..9).flat_map(|seat| game.hand(seat).to_vec()).collect();
+        assert_eq!(held.len(), 45);
+        assert!((0..9).all(|seat| game.cards_drawn(seat) == Some(3)));
+        assert_eq!(game.payouts().iter().sum::<u64>(), 9);
+        assert_eq!(
+            game.seats().iter().map(|seat| seat.stack()).sum::<u64>(),
+            900
+        );
+    }
+
+    #[test]
+    fn drawing_fails_when_the_discards_cannot_cover_the_draw() {
+        let config = LimitConfig {
+            ante: 0,
+            bring_in: 0,
+            small_bet: 2,
+            big_bet: 4,
+            max_bets: 4,
+        };
+        let mut game = DrawGame::new(config, &[100; 10], 0);
+        game.start_hand(7).unwrap();
+        while game.phase() == DrawPhase::FirstBetting {
+            game.act(Action::Check).unwrap();
+        }
+
+        let seat = game.to_act().unwrap();
+        let discards = game.hand(seat)[..3].to_vec();
+        assert_eq!(
+            game.draw(&discards),
+            Err(ActionError::Deck(DeckError::NotEnoughCards {
+                requested: 3,
+                remaining: 2,
+            }))
+        );
+        assert_eq!(game.hand(seat).len(), 5);
+        assert_eq!(game.deck().len(), 2);
+    }
+
+    #[test]
+    fn too_many_players_leave_the_game_untouched() {
+        let config = LimitConfig {
+            ante: 1,
+            bring_in: 0,
+            small_bet: 2,
+            big_bet: 4,
+            max_bets: 4,
+        };
+        let mut game = DrawGame::new(config, &[100; 11], 0);
+
+        assert_eq!(
+            game.start_hand(1),
+            Err(ActionError::Deck(DeckError::NotEnoughCards {
+                requested: 55,
+                remaining: 52,
+            }))
+        );
+        assert!(!game.is_hand_in_progress());
+        assert!(game.seats().iter().all(|seat| seat.stack() == 100));
+        assert!((0..11).all(|seat| game.hand(seat).is_empty()));
+    }
+}
@@ -0,0 +1,665 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    error::Error,
+    fmt,
+    sync::atomic::{self, AtomicUsize},
+    thread,
+};
+
+use super::{
+    fast::{evaluate_mask, CardMask, HandValue},
+    for_each_combination,
+    range::{Combo, Range},
+    rng::SeededRng,
+    Card, Rank, Ratio, Suit,
+};
+
+/// Share of runouts a player wins outright, splits and loses, in percent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equity {
+    pub win: f64,
+    pub tie: f64,
+    pub loss: f64,
+}
+
+/// Exact share of all possible runouts a player wins, splits and loses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactEquity {
+    pub win: Ratio,
+    pub tie: Ratio,
+    pub loss: Ratio,
+    /// Expected fraction of the pot, with each split pot divided evenly
+    /// between the tied players.
+    pub share: Ratio,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquityError {
+    NoPlayers,
+    /// A range has no combo left once the known cards are taken out.
+    EmptyRange,
+    BoardTooLong(usize),
+    DuplicateCard(Card),
+    /// The known cards leave too few in the deck to complete the board.
+    NotEnoughCards {
+        needed: usize,
+        remaining: usize,
+    },
+    /// A Monte Carlo estimate was asked for without any runouts.
+    NoRunouts,
+}
+
+impl fmt::Display for EquityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquityError::NoPlayers => write!(f, "at least one player is needed"),
+            EquityError::EmptyRange => write!(f, "a range has no combos left"),
+            EquityError::BoardTooLong(len) => {
+                write!(f, "the board has {} cards, at most 5 are allowed", len)
+            }
+            EquityError::DuplicateCard(card) => write!(f, "the card {} is used twice", card),
+            EquityError::NotEnoughCards { needed, remaining } => write!(
+                f,
+                "the board needs {} more cards, only {} remain",
+                needed, remaining
+            ),
+            EquityError::NoRunouts => write!(f, "at least one runout is needed"),
+        }
+    }
+}
+
+impl Error for EquityError {}
+
+/// Estimates Hold'em equities by dealing `runouts` random completions of the
+/// board. `board` may hold anywhere from zero to five cards and `dead` lists
+/// cards known to be out of the deck. The same seed always gives the same
+/// result.
+pub fn monte_carlo(
+    hole_cards: &[[Card; 2]],
+    board: &[Card],
+    dead: &[Card],
+    runouts: usize,
+    seed: u64,
+) -> Result<Vec<Equity>, EquityError> {
+    if hole_cards.is_empty() {
+        return Err(EquityError::NoPlayers);
+    }
+    if runouts == 0 {
+        return Err(EquityError::NoRunouts);
+    }
+
+    let mut deck = get_remaining_deck(hole_cards, board, dead)?;
+    let mut rng = SeededRng::new(seed);
+    let mut tally = Tally::new(hole_cards.len());
+
+    let mut showdown = Showdown::new(hole_cards, board);
+
+    for _ in 0..runouts {
+        let mut full_board = showdown.board;
+
+        // Partial Fisher-Yates, only the cards that are dealt get shuffled.
+        for i in 0..5 - board.len() {
+            let j = i + rng.below(deck.len() - i);
+            deck.swap(i, j);
+            full_board.insert(deck[i]);
+        }
+
+        tally.record(showdown.winners(full_board));
+    }
+
+    Ok(tally.to_equities())
+}
+
+/// Computes exact Hold'em equities by walking every possible completion of
+/// the board. Meant for spots with few unknown cards, e.g. the turn or river;
+/// a preflop heads-up enumeration deals 1,712,304 boards.
+pub fn exact(
+    hole_cards: &[[Card; 2]],
+    board: &[Card],
+    dead: &[Card],
+) -> Result<Vec<ExactEquity>, EquityError> {
+    if hole_cards.is_empty() {
+        return Err(EquityError::NoPlayers);
+    }
+
+    let deck = get_remaining_deck(hole_cards, board, dead)?;
+    let mut tally = Tally::new(hole_cards.len());
+
+    let mut showdown = Showdown::new(hole_cards, board);
+
+    for_each_combination(deck.len(), 5 - board.len(), |indexes| {
+        let mut full_board = showdown.board;
+        for i in indexes {
+            full_board.insert(deck[*i]);
+        }
+
+        tally.record(showdown.winners(full_board));
+    });
+
+    Ok(tally.to_exact_equities())
+}
+
+/// Equity of one combo against the whole opposing range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComboEquity {
+    pub combo: Combo,
+    /// Weight of the combo in its range.
+    pub weight: f64,
+    /// Expected share of the pot in percent, averaged over the opposing
+    /// combos it does not share a card with.
+    pub equity: f64,
+}
+
+/// Result of [`range_vs_range`]. Combos blocked by the board, the dead cards
+/// or every combo of the other range are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeEquity {
+    /// Expected share of the pot of the first range, in percent.
+    pub hero: f64,
+    pub villain: f64,
+    pub hero_combos: Vec<ComboEquity>,
+    pub villain_combos: Vec<ComboEquity>,
+}
+
+/// How [`range_vs_range`] deals the rest of the board and splits the work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeEquityOptions {
+    /// Random runouts dealt for each pair of combos, or `None` to walk every
+    /// runout, which is only practical from the flop on.
+    pub runouts: Option<usize>,
+    pub seed: u64,
+    pub threads: usize,
+}
+
+impl Default for RangeEquityOptions {
+    fn default() -> Self {
+        RangeEquityOptions {
+            runouts: None,
+            seed: 0,
+            threads: thread::available_parallelism().map_or(1, usize::from),
+        }
+    }
+}
+
+/// Computes the Hold'em equity of one range against another. Every pair of
+/// combos that share no card is played out and weighted by the product of
+/// the combo weights, so each range blocks the combos of the other. The
+/// combos of the first range are spread over the threads.
+pub fn range_vs_range(
+    hero: &Range,
+    villain: &Range,
+    board: &[Card],
+    dead: &[Card],
+    options: RangeEquityOptions,
+) -> Result<RangeEquity, EquityError> {
+    if options.runouts == Some(0) {
+        return Err(EquityError::NoRunouts);
+    }
+
+    let deck = get_remaining_deck(&[], board, dead)?;
+
+    let known: Vec<Card> = board.iter().chain(dead).copied().collect();
+    let heroes: Vec<(Combo, f64)> = hero.without_cards(&known).iter().collect();
+    let villains: Vec<(Combo, f64)> = villain.without_cards(&known).iter().collect();
+    if heroes.is_empty() || villains.is_empty() {
+        return Err(EquityError::EmptyRange);
+    }
+
+    let matchups = Matchups {
+        villains: &villains,
+        board: CardMask::from_cards(board),
+        cards_to_deal: 5 - board.len(),
+        deck: &deck,
+        options,
+    };
+
+    // Pot share of every hero combo against every villain combo, `None` when
+    // the two share a card.
+    let mut shares: Vec<Vec<Option<f64>>> = vec![Vec::new(); heroes.len()];
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut rows: Vec<(usize, Vec<Option<f64>>)> = Vec::new();
+
+                    loop {
+                        let index = next.fetch_add(1, atomic::Ordering::Relaxed);
+                        if index >= heroes.len() {
+                            return rows;
+                        }
+                        rows.push((index, matchups.play(index, heroes[index].0)));
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            for (index, row) in worker.join().unwrap() {
+                shares[index] = row;
+            }
+        }
+    });
+
+    let mut hero_totals = vec![(0.0, 0.0); heroes.len()];
+    let mut villain_totals = vec![(0.0, 0.0); villains.len()];
+
+    for (h, row) in shares.iter().enumerate() {
+        for (v, share) in row.iter().enumerate() {
+            if let Some(share) = share {
+                let (hero_weight, villain_weight) = (heroes[h].1, villains[v].1);
+
+                hero_totals[h].0 += share * villain_weight;
+                hero_totals[h].1 += villain_weight;
+                villain_totals[v].0 += (1.0 - share) * hero_weight;
+                villain_totals[v].1 += hero_weight;
+            }
+        }
+    }
+
+    let hero_combos = to_combo_equities(&heroes, &hero_totals);
+    let villain_combos = to_combo_equities(&villains, &villain_totals);
+
+    let (won, weight) = heroes.iter().zip(&hero_totals).fold(
+        (0.0, 0.0),
+        |(won, weight), ((_, hero_weight), total)| {
+            (won + hero_weight * total.0, weight + hero_weight * total.1)
+        },
+    );
+    if weight == 0.0 {
+        return Err(EquityError::EmptyRange);
+    }
+
+    Ok(RangeEquity {
+        hero: won * 100.0 / weight,
+        villain: (weight - won) * 100.0 / weight,
+        hero_combos,
+        villain_combos,
+    })
+}
+
+fn to_combo_equities(combos: &[(Combo, f64)], totals: &[(f64, f64)]) -> Vec<ComboEquity> {
+    combos
+        .iter()
+        .zip(totals)
+        .filter(|(_, (_, weight))| *weight > 0.0)
+        .map(|((combo, weight), (won, total))| ComboEquity {
+            combo: *combo,
+            weight: *weight,
+            equity: won * 100.0 / total,
+        })
+        .collect()
+}
+
+// Plays one hero combo against every villain combo of a range.
+struct Matchups<'a> {
+    villains: &'a [(Combo, f64)],
+    board: CardMask,
+    cards_to_deal: usize,
+    // Cards that are neither on the board nor dead.
+    deck: &'a [Card],
+    options: RangeEquityOptions,
+}
+
+impl Matchups<'_> {
+    fn play(&self, index: usize, hero: Combo) -> Vec<Option<f64>> {
+        let hero_mask = CardMask::from_cards(&hero.cards());
+        let deck: Vec<Card> = self
+            .deck
+            .iter()
+            .filter(|card| !hero_mask.contains(**card))
+            .copied()
+            .collect();
+        let villain_masks: Vec<Option<CardMask>> = self
+            .villains
+            .iter()
+            .map(|(villain, _)| {
+                let mask = CardMask::from_cards(&villain.cards());
+                (mask & hero_mask).is_empty().then_some(mask)
+            })
+            .collect();
+
+        let mut won = vec![0.0; self.villains.len()];
+        let mut played = vec![0u64; self.villains.len()];
+
+        let mut record = |v: usize, hero: HandValue, villain: HandValue| {
+            won[v] += match hero.cmp(&villain) {
+                Ordering::Greater => 1.0,
+                Ordering::Equal => 0.5,
+                Ordering::Less => 0.0,
+            };
+            played[v] += 1;
+        };
+
+        match self.options.runouts {
+            None => for_each_combination(deck.len(), self.cards_to_deal, |indexes| {
+                let mut board = self.board;
+                for i in indexes {
+                    board.insert(deck[*i]);
+                }
+                let hero_value = evaluate_mask(hero_mask | board);
+
+                for (v, villain) in villain_masks.iter().enumerate() {
+                    if let Some(villain) = villain {
+                        if (*villain & board).is_empty() {
+                            record(v, hero_value, evaluate_mask(*villain | board));
+                        }
+                    }
+                }
+            }),
+            Some(runouts) => {
+                // Seeded per hero combo so results do not depend on how the
+                // combos were spread over the threads.
+                let mut rng = SeededRng::new(self.options.seed ^ index as u64);
+
+                for (v, villain) in villain_masks.iter().enumerate() {
+                    let villain = match villain {
+                        Some(villain) => *villain,
+                        None => continue,
+                    };
+                    let mut deck: Vec<Card> = deck
+                        .iter()
+                        .filter(|card| !villain.contains(**card))
+                        .copied()
+                        .collect();
+
+                    for _ in 0..runouts {
+                        let mut board = self.board;
+                        for i in 0..self.cards_to_deal {
+                            let j = i + rng.below(deck.len() - i);
+                            deck.swap(i, j);
+                            board.insert(deck[i]);
+                        }
+                        record(
+                            v,
+                            evaluate_mask(hero_mask | board),
+                            evaluate_mask(villain | board),
+                        );
+                    }
+                }
+            }
+        }
+
+        won.iter()
+            .zip(&played)
+            .map(|(won, played)| (*played > 0).then(|| won / *played as f64))
+            .collect()
+    }
+}
+
+// Finds the winning seats of a runout with the table-driven evaluator, reusing
+// the same buffer for every runout.
+struct Showdown {
+    board: CardMask,
+    hole_masks: Vec<CardMask>,
+    winners: Vec<usize>,
+}
+
+impl Showdown {
+    fn new(hole_cards: &[[Card; 2]], board: &[Card]) -> Self {
+        Showdown {
+            board: CardMask::from_cards(board),
+            hole_masks: hole_cards
+                .iter()
+                .map(|cards| CardMask::from_cards(cards))
+                .collect(),
+            winners: Vec::with_capacity(hole_cards.len()),
+        }
+    }
+
+    fn winners(&mut self, board: CardMask) -> &[usize] {
+        let mut best: Option<HandValue> = None;
+        self.winners.clear();
+
+        for (seat, hole) in self.hole_masks.iter().enumerate() {
+            let value = evaluate_mask(*hole | board);
+
+            if best.is_none_or(|best| value > best) {
+                best = Some(value);
+                self.winners.clear();
+            }
+            if best == Some(value) {
+                self.winners.push(seat);
+            }
+        }
+
+        &self.winners
+    }
+}
+
+struct Tally {
+    wins: Vec<u64>,
+    ties: Vec<u64>,
+    // Number of split pots for each seat, indexed by how many players split it.
+    splits: Vec<Vec<u64>>,
+    total: u64,
+}
+
+impl Tally {
+    fn new(players: usize) -> Self {
+        Tally {
+            wins: vec![0; players],
+            ties: vec![0; players],
+            splits: vec![vec![0; players + 1]; players],
+            total: 0,
+        }
+    }
+
+    fn record(&mut self, winners: &[usize]) {
+        for seat in winners {
+            if winners.len() == 1 {
+                self.wins[*seat] += 1;
+            } else {
+                self.ties[*seat] += 1;
+                self.splits[*seat][winners.len()] += 1;
+            }
+        }
+
+        self.total += 1;
+    }
+
+    fn to_equities(&self) -> Vec<Equity> {
+        let total = self.total.max(1) as f64;
+
+        self.wins
+            .iter()
+            .zip(&self.ties)
+            .map(|(wins, ties)| Equity {
+                win: *wins as f64 * 100.0 / total,
+                tie: *ties as f64 * 100.0 / total,
+                loss: (self.total - wins - ties) as f64 * 100.0 / total,
+            })
+            .collect()
+    }
+
+    fn to_exact_equities(&self) -> Vec<ExactEquity> {
+        let total = self.total.max(1);
+
+        (0..self.wins.len())
+            .map(|seat| {
+                let wins = self.wins[seat];
+                let ties = self.ties[seat];
+
+                let mut share = Ratio::new(wins, total);
+                for (players, splits) in self.splits[seat].iter().enumerate() {
+                    if *splits > 0 {
+                        share = share + Ratio::new(*splits, players as u64 * total);
+                    }
+                }
+
+                ExactEquity {
+                    win: Ratio::new(wins, total),
+                    tie: Ratio::new(ties, total),
+                    loss: Ratio::new(self.total - wins - ties, total),
+                    share,
+                }
+            })
+            .collect()
+    }
+}
+
+// Every card of the deck that is not held, on the board or dead.
+fn get_remaining_deck(
+    hole_cards: &[[Card; 2]],
+    board: &[Card],
+    dead: &[Card],
+) -> Result<Vec<Card>, EquityError> {
+    if board.len() > 5 {
+        return Err(EquityError::BoardTooLong(board.len()));
+    }
+
+    let mut known: HashSet<Card> = HashSet::new();
+    let used = hole_cards.iter().flatten().chain(board).chain(dead);
+
+    for card in used {
+        if !known.insert(*card) {
+            return Err(EquityError::DuplicateCard(*card));
+        }
+    }
+
+    let mut deck: Vec<Card> = Vec::new();
+
+    for suit in Suit::ALL {
+        for rank in Rank::ALL {
+            let card = Card::new(rank, suit);
+
+            if !known.contains(&card) {
+                deck.push(card);
+            }
+        }
+    }
+
+    if deck.len() < 5 - board.len() {
+        return Err(EquityError::NotEnoughCards {
+            needed: 5 - board.len(),
+            remaining: deck.len(),
+        });
+    }
+
+    Ok(deck)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(hand: &str) -> Vec<Card> {
+        crate::poker::parse_hand(hand).unwrap()
+    }
+
+    fn hole_cards(hand: &str) -> [Card; 2] {
+        let cards = cards(hand);
+        [cards[0], cards[1]]
+    }
+
+    #[test]
+    fn exact_aces_against_kings_on_a_dry_flop() {
+        let players = [hole_cards("AS AH"), hole_cards("KS KH")];
+        let equities = exact(&players, &cards("2C 7D 9H"), &[]).unwrap();
+
+        assert_eq!(equities[0].win, Ratio::new(907, 990));
+        assert_eq!(equities[0].share, Ratio::new(907, 990));
+        assert_eq!(equities[1].win, Ratio::new(83, 990));
+        assert_eq!(equities[0].tie, Ratio::new(0, 1));
+    }
+
+    #[test]
+    fn exact_splits_a_board_that_plays() {
+        let players = [hole_cards("2C 3D"), hole_cards("2D 3C")];
+        let equities = exact(&players, &cards("AS KS QH JD TC"), &[]).unwrap();
+
+        assert_eq!(equities[0].tie, Ratio::new(1, 1));
+        assert_eq!(equities[0].share, Ratio::new(1, 2));
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_exact() {
+        let players = [
+            hole_cards("AS AH"),
+            hole_cards("KS KH"),
+            hole_cards("8C 9C"),
+        ];
+        let board = cards("2C 7D 9H");
+        let exact = exact(&players, &board, &[]).unwrap();
+        let estimate = monte_carlo(&players, &board, &[], 100_000, 7).unwrap();
+
+        for (exact, estimate) in exact.iter().zip(&estimate) {
+            assert!((exact.win.to_f64() * 100.0 - estimate.win).abs() < 0.5);
+            assert!((exact.tie.to_f64() * 100.0 - estimate.tie).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn too_few_cards_or_runouts_are_errors() {
+        let players = [hole_cards("AS AH"), hole_cards("KS KH")];
+        let dead: Vec<Card> = Suit::ALL
+            .iter()
+            .flat_map(|suit| Rank::ALL.iter().map(|rank| Card::new(*rank, *suit)))
+            .filter(|card| !players.iter().flatten().any(|held| held == card))
+            .skip(3)
+            .collect();
+        let short = EquityError::NotEnoughCards {
+            needed: 5,
+            remaining: 3,
+        };
+
+        assert_eq!(
+            monte_carlo(&players, &[], &dead, 1000, 1),
+            Err(short.clone())
+        );
+        assert_eq!(exact(&players, &[], &dead), Err(short));
+        assert_eq!(
+            monte_carlo(&players, &[], &[], 0, 1),
+            Err(EquityError::NoRunouts)
+        );
+    }
+
+    #[test]
+    fn aces_against_kings_before_the_flop() {
+        let options = RangeEquityOptions {
+            runouts: Some(2_000),
+            seed: 5,
+            threads: 2,
+        };
+        let equity = range_vs_range(
+            &"AA".parse().unwrap(),
+            &"KK".parse().unwrap(),
+            &[],
+            &[],
+            options,
+        )
+        .unwrap();
+
+        assert!((equity.hero - 81.7).abs() < 0.5, "{}", equity.hero);
+        assert!((equity.hero + equity.villain - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn range_equity_does_not_depend_on_the_threads() {
+        let hero: Range = "TT+, AK, KQs".parse().unwrap();
+        let villain: Range = "22+, AT+, KJs+".parse().unwrap();
+        let board = cards("AH 7C 2D");
+        let options = RangeEquityOptions {
+            runouts: Some(50),
+            seed: 9,
+            threads: 1,
+        };
+
+        let single = range_vs_range(&hero, &villain, &board, &[], options).unwrap();
+        let threaded = range_vs_range(
+            &hero,
+            &villain,
+            &board,
+            &[],
+            RangeEquityOptions {
+                threads: 4,
+                ..options
+            },
+        )
+        .unwrap();
+
+        assert_eq!(single, threaded);
+    }
+}
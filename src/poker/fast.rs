@@ -0,0 +1,306 @@
+use std::{
+    ops::{BitAnd, BitOr},
+    sync::OnceLock,
+};
+
+use super::{best_hand, Card, HandRank, HandType, Rank, Suit};
+
+const RANK_BITS: u64 = 0x1FFF;
+
+/// A set of cards packed into a `u64`, one 16 bit lane per suit with one bit
+/// per rank, deuce in the lowest bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CardMask(u64);
+
+impl CardMask {
+    pub fn from_cards(cards: &[Card]) -> Self {
+        cards.iter().fold(CardMask::default(), |mask, card| {
+            mask | CardMask::from(*card)
+        })
+    }
+
+    pub fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub fn contains(self, card: Card) -> bool {
+        self.0 & CardMask::from(card).0 != 0
+    }
+
+    pub fn insert(&mut self, card: Card) {
+        self.0 |= CardMask::from(card).0;
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn suit_lane(self, suit: usize) -> u32 {
+        ((self.0 >> (16 * suit)) & RANK_BITS) as u32
+    }
+}
+
+impl From<Card> for CardMask {
+    fn from(card: Card) -> Self {
+        CardMask(1 << (16 * card.suit as u64 + card.rank.value() as u64 - 2))
+    }
+}
+
+impl BitOr for CardMask {
+    type Output = CardMask;
+
+    fn bitor(self, other: CardMask) -> CardMask {
+        CardMask(self.0 | other.0)
+    }
+}
+
+impl BitAnd for CardMask {
+    type Output = CardMask;
+
+    fn bitand(self, other: CardMask) -> CardMask {
+        CardMask(self.0 & other.0)
+    }
+}
+
+/// Hand strength packed into a `u32`: the category in the top bits followed
+/// by up to five tie-break ranks, four bits each, in the same order as
+/// [`HandRank::ranks`]. Greater values are stronger hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandValue(u32);
+
+impl HandValue {
+    fn from_hand_rank(rank: &HandRank) -> Self {
+        let ranks = rank
+            .ranks
+            .iter()
+            .fold(0, |packed, rank| (packed << 4) | rank.value() as u32);
+
+        HandValue(((rank.hand_type as u32) << 20) | (ranks << (4 * (5 - rank.ranks.len()))))
+    }
+
+    pub fn hand_type(self) -> HandType {
+        match self.0 >> 20 {
+            0 => HandType::HighCard,
+            1 => HandType::OnePair,
+            2 => HandType::TwoPair,
+            3 => HandType::ThreeOfKind,
+            4 => HandType::Straight,
+            5 => HandType::Flush,
+            6 => HandType::FullHouse,
+            7 => HandType::FourOfKind,
+            8 => HandType::StraightFlush,
+            _ => HandType::FiveOfKind,
+        }
+    }
+
+    pub fn to_hand_rank(self) -> HandRank {
+        let ranks = (0..5)
+            .rev()
+            .filter_map(|i| Rank::from_value(((self.0 >> (4 * i)) & 0xF) as u8))
+            .collect();
+
+        HandRank {
+            hand_type: self.hand_type(),
+            ranks,
+        }
+    }
+}
+
+// One prime per rank from the deuce up, so the product of a hand's primes
+// identifies the ranks it holds and how often.
+const PRIMES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+struct Tables {
+    // Best flush or straight flush among the ranks of one suit, indexed by
+    // the suit's rank mask, for five to seven cards.
+    flushes: Vec<Option<HandValue>>,
+    // Every hand without a flush, keyed by its prime product in an open
+    // addressing table of `PRODUCT_SLOTS` slots, 0 marking an empty slot.
+    products: Vec<(u64, HandValue)>,
+}
+
+// A power of two well above the 75,000 or so hands without a flush, which
+// keeps probe sequences short.
+const PRODUCT_SLOTS: usize = 1 << 18;
+
+// First slot to probe for a prime product.
+fn get_slot(product: u64) -> usize {
+    (product.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (64 - PRODUCT_SLOTS.trailing_zeros())) as usize
+}
+
+// Both tables are filled in by the reference evaluator, so they agree with it
+// by construction.
+fn get_tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let mut flushes = vec![None; 1 << 13];
+        for (ranks, flush) in flushes.iter_mut().enumerate() {
+            if (5..=7).contains(&ranks.count_ones()) {
+                let cards: Vec<Card> = Rank::ALL
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| ranks & (1 << i) != 0)
+                    .map(|(_, rank)| Card::new(*rank, Suit::Spades))
+                    .collect();
+                *flush = Some(HandValue::from_hand_rank(&best_hand(&cards).0));
+            }
+        }
+
+        let mut products = vec![(0, HandValue(0)); PRODUCT_SLOTS];
+        add_rank_counts(&mut [0; 13], 0, &mut products);
+
+        Tables { flushes, products }
+    })
+}
+
+// Goes through every way of holding each rank up to four times with five to
+// seven cards in all, from rank `next` on.
+fn add_rank_counts(counts: &mut [u32; 13], next: usize, products: &mut Vec<(u64, HandValue)>) {
+    let total: u32 = counts.iter().sum();
+
+    if next == counts.len() {
+        if total >= 5 {
+            // Dealing the suits in turn keeps a rank's cards apart and leaves
+            // at most two cards per suit.
+            let cards: Vec<Card> = Rank::ALL
+                .iter()
+                .zip(counts.iter())
+                .flat_map(|(rank, count)| std::iter::repeat_n(*rank, *count as usize))
+                .zip(Suit::ALL.iter().cycle())
+                .map(|(rank, suit)| Card::new(rank, *suit))
+                .collect();
+            let product = (0..counts.len())
+                .map(|i| PRIMES[i].pow(counts[i]))
+                .product();
+
+            let mut slot = get_slot(product);
+            while products[slot].0 != 0 {
+                slot = (slot + 1) % PRODUCT_SLOTS;
+            }
+            products[slot] = (product, HandValue::from_hand_rank(&best_hand(&cards).0));
+        }
+        return;
+    }
+
+    for count in 0..=(7 - total).min(4) {
+        counts[next] = count;
+        add_rank_counts(counts, next + 1, products);
+    }
+    counts[next] = 0;
+}
+
+/// Evaluates the best five card hand within five to seven cards without
+/// allocating. A suit holding five cards is looked up by its rank mask, any
+/// other hand by the product of the primes of its ranks. Agrees with
+/// [`super::best_hand`] under high rules.
+///
+/// # Panics
+///
+/// When the mask holds fewer than five or more than seven cards.
+pub fn evaluate_mask(mask: CardMask) -> HandValue {
+    let tables = get_tables();
+
+    for suit in 0..4 {
+        let lane = mask.suit_lane(suit);
+        // With seven cards at most, a flush beats anything the other two
+        // cards could make.
+        if lane.count_ones() >= 5 {
+            if let Some(value) = tables.flushes[lane as usize] {
+                return value;
+            }
+        }
+    }
+
+    let mut product = 1;
+    let mut bits = mask.0;
+    while bits != 0 {
+        product *= PRIMES[(bits.trailing_zeros() % 16) as usize];
+        bits &= bits - 1;
+    }
+
+    let mut slot = get_slot(product);
+    loop {
+        match tables.products[slot] {
+            (key, value) if key == product => return value,
+            (0, _) => panic!(
+                "hands are evaluated from five to seven cards, not {}",
+                mask.len()
+            ),
+            _ => slot = (slot + 1) % PRODUCT_SLOTS,
+        }
+    }
+}
+
+/// Same as [`evaluate_mask`] for a slice of cards.
+pub fn evaluate_cards(cards: &[Card]) -> HandValue {
+    evaluate_mask(CardMask::from_cards(cards))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{hint::black_box, time::Instant};
+
+    use super::*;
+    use crate::poker::rng::SeededRng;
+
+    fn random_hands(count: usize, size: usize, seed: u64) -> Vec<Vec<Card>> {
+        let mut rng = SeededRng::new(seed);
+        let mut deck: Vec<Card> = Suit::ALL
+            .iter()
+            .flat_map(|suit| Rank::ALL.iter().map(|rank| Card::new(*rank, *suit)))
+            .collect();
+
+        (0..count)
+            .map(|_| {
+                rng.shuffle(&mut deck);
+                deck[..size].to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn agrees_with_the_reference_evaluator() {
+        for size in 5..=7 {
+            let hands = random_hands(30_000, size, size as u64);
+            let mut previous: Option<(HandValue, HandRank)> = None;
+
+            for hand in hands {
+                let value = evaluate_cards(&hand);
+                let rank = best_hand(&hand).0;
+                assert_eq!(value.to_hand_rank(), rank, "{:?}", hand);
+
+                if let Some((other_value, other_rank)) = previous {
+                    assert_eq!(value.cmp(&other_value), rank.cmp(&other_rank), "{:?}", hand);
+                }
+                previous = Some((value, rank));
+            }
+        }
+    }
+
+    // Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn throughput() {
+        let samples: Vec<CardMask> = random_hands(1 << 16, 7, 42)
+            .iter()
+            .map(|hand| CardMask::from_cards(hand))
+            .collect();
+        let hands = 10_000_000;
+
+        evaluate_mask(samples[0]);
+        let start = Instant::now();
+        for i in 0..hands {
+            black_box(evaluate_mask(black_box(samples[i % samples.len()])));
+        }
+
+        println!(
+            "{:.0} hands per second",
+            hands as f64 / start.elapsed().as_secs_f64()
+        );
+    }
+}
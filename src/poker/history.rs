@@ -0,0 +1,1129 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead},
+};
+
+use super::{
+    best_hand,
+    notation::{self, CardNotation},
+    omaha_best_hand,
+    replay::{HandRecord, Replay},
+    table::{Action, ActionError, Limit, Post, Street, Table},
+    Card, HandRank, ParseCardError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Position {
+    Button,
+    SmallBlind,
+    BigBlind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryPlayer {
+    pub seat: usize,
+    pub name: String,
+    /// Starting stack, in hundredths of the currency unit or of a chip.
+    pub stack: u64,
+    pub position: Option<Position>,
+    /// Hole cards, when they were dealt to the hero or shown down.
+    pub hole_cards: Option<Vec<Card>>,
+    pub sitting_out: bool,
+}
+
+/// Amounts are in hundredths of the currency unit or of a chip, so `$0.25`
+/// and `25` chips read as 25 and 2500.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionKind {
+    PostAnte(u64),
+    PostSmallBlind(u64),
+    PostBigBlind(u64),
+    PostDeadBlinds(u64),
+    Fold,
+    Check,
+    Call(u64),
+    Bet(u64),
+    Raise { by: u64, to: u64 },
+    UncalledBetReturned(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryAction {
+    pub street: Street,
+    pub player: String,
+    pub kind: ActionKind,
+    pub all_in: bool,
+}
+
+/// One hand read from a hand history log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistory {
+    /// Line of the log the hand starts on, counting from 1.
+    pub line: usize,
+    pub hand_id: String,
+    /// Game description from the header, e.g. `Hold'em No Limit ($0.01/$0.02 USD)`.
+    pub game: String,
+    pub table: String,
+    pub max_seats: Option<usize>,
+    /// Seat number of the button.
+    pub button: usize,
+    pub players: Vec<HistoryPlayer>,
+    pub board: Vec<Card>,
+    pub actions: Vec<HistoryAction>,
+    /// Chips collected by each winning player.
+    pub winnings: Vec<(String, u64)>,
+    pub total_pot: Option<u64>,
+    pub rake: Option<u64>,
+}
+
+impl HandHistory {
+    pub fn player(&self, name: &str) -> Option<&HistoryPlayer> {
+        self.players.iter().find(|player| player.name == name)
+    }
+
+    /// Re-evaluates the hands shown down on a complete board and returns the
+    /// names of the players holding the best of them, so they can be checked
+    /// against the recorded winnings. Four hole cards are played as Omaha.
+    pub fn showdown_winners(&self) -> Vec<&str> {
+        if self.board.len() != 5 {
+            return Vec::new();
+        }
+
+        let folded: Vec<&str> = self
+            .actions
+            .iter()
+            .filter(|action| action.kind == ActionKind::Fold)
+            .map(|action| action.player.as_str())
+            .collect();
+
+        let mut shown: Vec<(&str, HandRank)> = Vec::new();
+
+        for player in &self.players {
+            let hole_cards = match &player.hole_cards {
+                Some(cards) if !folded.contains(&player.name.as_str()) => cards,
+                _ => continue,
+            };
+
+            let rank = match hole_cards.len() {
+                4 => {
+                    let hole = [hole_cards[0], hole_cards[1], hole_cards[2], hole_cards[3]];
+                    omaha_best_hand(&hole, &self.board).0
+                }
+                _ => {
+                    let mut cards = hole_cards.clone();
+                    cards.extend_from_slice(&self.board);
+                    best_hand(&cards).0
+                }
+            };
+
+            shown.push((&player.name, rank));
+        }
+
+        let best = match shown.iter().map(|(_, rank)| rank).max() {
+            Some(best) => best.clone(),
+            None => return Vec::new(),
+        };
+
+        shown
+            .into_iter()
+            .filter(|(_, rank)| *rank == best)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum HistoryErrorKind {
+    Io(io::Error),
+    MissingHeader,
+    Malformed(String),
+    InvalidCard(ParseCardError),
+    InvalidAmount(String),
+    UnknownPlayer(String),
+}
+
+/// Error raised while reading a hand history, with the line it occurred on.
+#[derive(Debug)]
+pub struct HistoryError {
+    line: usize,
+    kind: HistoryErrorKind,
+}
+
+impl HistoryError {
+    fn new(line: usize, kind: HistoryErrorKind) -> Self {
+        HistoryError { line, kind }
+    }
+
+    /// Line of the log, counting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn kind(&self) -> &HistoryErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            HistoryErrorKind::Io(err) => write!(f, "{}", err),
+            HistoryErrorKind::MissingHeader => write!(f, "expected a hand header"),
+            HistoryErrorKind::Malformed(line) => write!(f, "malformed line {:?}", line),
+            HistoryErrorKind::InvalidCard(err) => write!(f, "{}", err),
+            HistoryErrorKind::InvalidAmount(amount) => write!(f, "invalid amount {:?}", amount),
+            HistoryErrorKind::UnknownPlayer(name) => write!(f, "unknown player {:?}", name),
+        }
+    }
+}
+
+impl Error for HistoryError {}
+
+/// Reads PokerStars style text hand histories one hand at a time, so logs of
+/// any size can be processed. A hand that fails to parse yields an error and
+/// the reader moves on to the next one.
+pub struct HistoryReader<R: BufRead> {
+    lines: io::Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> HistoryReader<R> {
+    pub fn new(reader: R) -> Self {
+        HistoryReader {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> Option<Result<(usize, String), HistoryError>> {
+        let line = self.lines.next()?;
+        self.line += 1;
+
+        Some(match line {
+            Ok(line) => Ok((
+                self.line,
+                line.trim_start_matches('\u{feff}').trim_end().to_string(),
+            )),
+            Err(err) => Err(HistoryError::new(self.line, HistoryErrorKind::Io(err))),
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for HistoryReader<R> {
+    type Item = Result<HandHistory, HistoryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut lines: Vec<(usize, String)> = Vec::new();
+
+        // A hand runs until the next blank line.
+        loop {
+            match self.next_line() {
+                None => break,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok((_, line))) if line.is_empty() => {
+                    if lines.is_empty() {
+                        continue;
+                    }
+                    break;
+                }
+                Some(Ok(line)) => lines.push(line),
+            }
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        Some(parse_hand_history(&lines))
+    }
+}
+
+/// Labels a written hand history needs besides the hand itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryHeader {
+    pub hand_id: String,
+    pub table: String,
+    /// Written as is after the game, e.g. `2024/01/31 20:15:00 ET`.
+    pub date: String,
+    /// Player names by seat. Seats without a name are called `Player 1`,
+    /// `Player 2` and so on.
+    pub players: Vec<String>,
+}
+
+/// Plays a recorded hand again and writes it as a PokerStars style text hand
+/// history that [`HistoryReader`] reads back. Every player's hole cards are
+/// listed.
+///
+/// A chip of the table is written as a cent, e.g. a 25 chip bet as `$0.25`,
+/// so the amounts read back are the chip counts of the table.
+pub fn write_hand_history(
+    record: &HandRecord,
+    header: &HistoryHeader,
+) -> Result<String, ActionError> {
+    let mut replay = Replay::new(record.clone())?;
+    let table = replay.table();
+    let seats = record.stacks.len();
+    let names: Vec<String> = (0..seats)
+        .map(|seat| match header.players.get(seat) {
+            Some(name) => name.clone(),
+            None => format!("Player {}", seat + 1),
+        })
+        .collect();
+    let posted = |post: Post| {
+        table
+            .posts()
+            .iter()
+            .find(|record| record.post == post)
+            .map(|record| record.seat)
+    };
+    let small_blind = posted(Post::SmallBlind);
+    let big_blind = posted(Post::BigBlind);
+    let button = table.button();
+
+    let limit = match record.config.limit {
+        Limit::NoLimit => "No Limit",
+        Limit::PotLimit => "Pot Limit",
+    };
+    let mut lines: Vec<String> = vec![
+        format!(
+            "PokerStars Hand #{}:  Hold'em {} ({}/{} USD) - {}",
+            header.hand_id,
+            limit,
+            format_amount(record.config.small_blind),
+            format_amount(record.config.big_blind),
+            header.date
+        ),
+        format!(
+            "Table '{}' {}-max Seat #{} is the button",
+            header.table,
+            seats,
+            button + 1
+        ),
+    ];
+
+    for (seat, stack) in record.stacks.iter().enumerate() {
+        if *stack > 0 {
+            lines.push(format!(
+                "Seat {}: {} ({} in chips)",
+                seat + 1,
+                names[seat],
+                format_amount(*stack)
+            ));
+        }
+    }
+
+    // Chips each seat put in as far as the written lines go, to spot the
+    // uncalled bets the table hands back.
+    let mut contributed = vec![0; seats];
+
+    for post in table.posts() {
+        let verb = match post.post {
+            Post::Ante => "posts the ante",
+            Post::SmallBlind => "posts small blind",
+            Post::BigBlind => "posts big blind",
+        };
+        let all_in = if table.seats()[post.seat].stack() == 0 {
+            " and is all-in"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "{}: {} {}{}",
+            names[post.seat],
+            verb,
+            format_amount(post.amount),
+            all_in
+        ));
+        contributed[post.seat] += post.amount;
+    }
+
+    lines.push("*** HOLE CARDS ***".to_string());
+    for (seat, state) in table.seats().iter().enumerate() {
+        if let Some(cards) = state.hole_cards() {
+            lines.push(format!(
+                "Dealt to {} [{}]",
+                names[seat],
+                format_cards(&cards)
+            ));
+        }
+    }
+    write_uncalled_bets(&mut lines, &names, table, &mut contributed);
+    write_streets(&mut lines, &[], table.board());
+
+    loop {
+        let table = replay.table().clone();
+        let board = table.board().to_vec();
+        let current_bet = table
+            .seats()
+            .iter()
+            .map(|seat| seat.street_bet())
+            .max()
+            .unwrap_or(0);
+        let to_call = table.legal_actions().map_or(0, |legal| legal.to_call);
+
+        let record = match replay.step()? {
+            Some(record) => record,
+            None => break,
+        };
+        let before = &table.seats()[record.seat];
+
+        let (verb, put_in) = match record.action {
+            Action::Fold => ("folds".to_string(), 0),
+            Action::Check => ("checks".to_string(), 0),
+            Action::Call => (format!("calls {}", format_amount(to_call)), to_call),
+            Action::Bet(to) => (
+                format!("bets {}", format_amount(to)),
+                to - before.street_bet(),
+            ),
+            Action::Raise(to) => (
+                format!(
+                    "raises {} to {}",
+                    format_amount(to - current_bet),
+                    format_amount(to)
+                ),
+                to - before.street_bet(),
+            ),
+        };
+        let all_in = if put_in > 0 && put_in == before.stack() {
+            " and is all-in"
+        } else {
+            ""
+        };
+        lines.push(format!("{}: {}{}", names[record.seat], verb, all_in));
+        contributed[record.seat] += put_in;
+
+        let table = replay.table();
+        write_uncalled_bets(&mut lines, &names, table, &mut contributed);
+        write_streets(&mut lines, &board, table.board());
+    }
+
+    let table = replay.table();
+    let showdown = table.street() == Street::Showdown;
+
+    if showdown {
+        lines.push("*** SHOW DOWN ***".to_string());
+        for (seat, state) in table.seats().iter().enumerate() {
+            if let (true, Some(cards)) = (state.is_live(), state.hole_cards()) {
+                lines.push(format!("{}: shows [{}]", names[seat], format_cards(&cards)));
+            }
+        }
+    }
+    for (seat, payout) in table.payouts().iter().enumerate() {
+        if *payout > 0 {
+            lines.push(format!(
+                "{} collected {} from pot",
+                names[seat],
+                format_amount(*payout)
+            ));
+            if !showdown {
+                lines.push(format!("{}: doesn't show hand", names[seat]));
+            }
+        }
+    }
+
+    lines.push("*** SUMMARY ***".to_string());
+    let total: u64 = table.payouts().iter().sum();
+    let pots = match table.pots() {
+        [_] | [] => String::new(),
+        [main, sides @ ..] => {
+            let mut pots = format!(" Main pot {}.", format_amount(main.amount));
+            for (i, side) in sides.iter().enumerate() {
+                pots.push_str(&format!(
+                    " Side pot-{} {}.",
+                    i + 1,
+                    format_amount(side.amount)
+                ));
+            }
+            pots
+        }
+    };
+    lines.push(format!(
+        "Total pot {}{} | Rake $0",
+        format_amount(total),
+        pots
+    ));
+    if !table.board().is_empty() {
+        lines.push(format!("Board [{}]", format_cards(table.board())));
+    }
+
+    for (seat, state) in table.seats().iter().enumerate() {
+        if record.stacks[seat] == 0 {
+            continue;
+        }
+
+        let position = if seat == button {
+            " (button)"
+        } else if Some(seat) == small_blind {
+            " (small blind)"
+        } else if Some(seat) == big_blind {
+            " (big blind)"
+        } else {
+            ""
+        };
+        let payout = table.payouts()[seat];
+        let folded_on = table
+            .actions()
+            .iter()
+            .find(|action| action.seat == seat && action.action == Action::Fold)
+            .map(|action| action.street);
+
+        let outcome = match (folded_on, state.hole_cards()) {
+            (Some(Street::Preflop), _) => "folded before Flop".to_string(),
+            (Some(street), _) => format!("folded on the {}", street_name(street)),
+            (None, Some(cards)) if showdown && payout > 0 => {
+                format!(
+                    "showed [{}] and won ({})",
+                    format_cards(&cards),
+                    format_amount(payout)
+                )
+            }
+            (None, Some(cards)) if showdown => {
+                format!("showed [{}] and lost", format_cards(&cards))
+            }
+            _ => format!("collected ({})", format_amount(payout)),
+        };
+        lines.push(format!(
+            "Seat {}: {}{} {}",
+            seat + 1,
+            names[seat],
+            position,
+            outcome
+        ));
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    Ok(text)
+}
+
+// Street markers for the cards dealt since the board held `before`, e.g.
+// "*** TURN *** [2c 7h Qs] [Jd]".
+fn write_streets(lines: &mut Vec<String>, before: &[Card], board: &[Card]) {
+    for (len, street) in [(3, Street::Flop), (4, Street::Turn), (5, Street::River)] {
+        if before.len() >= len || board.len() < len {
+            continue;
+        }
+
+        let name = street_name(street).to_uppercase();
+        let line = match len {
+            3 => format!("*** {} *** [{}]", name, format_cards(&board[..3])),
+            _ => format!(
+                "*** {} *** [{}] [{}]",
+                name,
+                format_cards(&board[..len - 1]),
+                format_cards(&board[len - 1..len])
+            ),
+        };
+        lines.push(line);
+    }
+}
+
+fn write_uncalled_bets(
+    lines: &mut Vec<String>,
+    names: &[String],
+    table: &Table,
+    contributed: &mut [u64],
+) {
+    for (seat, state) in table.seats().iter().enumerate() {
+        if state.contributed() < contributed[seat] {
+            lines.push(format!(
+                "Uncalled bet ({}) returned to {}",
+                format_amount(contributed[seat] - state.contributed()),
+                names[seat]
+            ));
+            contributed[seat] = state.contributed();
+        }
+    }
+}
+
+fn street_name(street: Street) -> &'static str {
+    match street {
+        Street::Preflop => "Preflop",
+        Street::Flop => "Flop",
+        Street::Turn => "Turn",
+        Street::River => "River",
+        Street::Showdown => "Showdown",
+    }
+}
+
+// Chips as cents, the unit amounts are read in, e.g. "$0.25".
+fn format_amount(chips: u64) -> String {
+    format!("${}.{:02}", chips / 100, chips % 100)
+}
+
+// PokerStars writes tens as "T" and suits in lower case, e.g. "Ah Td".
+fn format_cards(cards: &[Card]) -> String {
+    notation::format_cards(cards, CardNotation::Compact, " ")
+}
+
+fn parse_hand_history(lines: &[(usize, String)]) -> Result<HandHistory, HistoryError> {
+    let (first_line, header) = &lines[0];
+    let malformed = |line: usize, text: &str| {
+        HistoryError::new(line, HistoryErrorKind::Malformed(text.to_string()))
+    };
+
+    let header_rest = match header.strip_prefix("PokerStars ") {
+        Some(rest) => rest,
+        None => {
+            return Err(HistoryError::new(
+                *first_line,
+                HistoryErrorKind::MissingHeader,
+            ))
+        }
+    };
+    let (hand_id, game) = match header_rest
+        .split_once('#')
+        .and_then(|(_, rest)| rest.split_once(':'))
+    {
+        Some((id, rest)) => {
+            let game = rest.trim().split(" - ").next().unwrap_or("");
+            (id.trim().to_string(), game.to_string())
+        }
+        None => return Err(malformed(*first_line, header)),
+    };
+
+    let mut history = HandHistory {
+        line: *first_line,
+        hand_id,
+        game,
+        table: String::new(),
+        max_seats: None,
+        button: 0,
+        players: Vec::new(),
+        board: Vec::new(),
+        actions: Vec::new(),
+        winnings: Vec::new(),
+        total_pot: None,
+        rake: None,
+    };
+
+    let mut street = Street::Preflop;
+    let mut in_summary = false;
+
+    for (number, line) in &lines[1..] {
+        let number = *number;
+
+        if let Some(marker) = line.strip_prefix("*** ") {
+            if marker.starts_with("FLOP") {
+                street = Street::Flop;
+            } else if marker.starts_with("TURN") {
+                street = Street::Turn;
+            } else if marker.starts_with("RIVER") {
+                street = Street::River;
+            } else if marker.starts_with("SHOW DOWN") {
+                street = Street::Showdown;
+            } else if marker.starts_with("SUMMARY") {
+                in_summary = true;
+            }
+
+            if street != Street::Preflop && !in_summary && marker.contains('[') {
+                history.board = parse_bracketed_cards(number, line, true)?;
+            }
+            continue;
+        }
+
+        if in_summary {
+            parse_summary_line(number, line, &mut history)?;
+        } else if let Some(rest) = line.strip_prefix("Table '") {
+            let (table, rest) = match rest.split_once('\'') {
+                Some(parts) => parts,
+                None => return Err(malformed(number, line)),
+            };
+            history.table = table.to_string();
+            history.max_seats = rest
+                .split_whitespace()
+                .find_map(|word| word.strip_suffix("-max"))
+                .and_then(|max| max.parse().ok());
+            history.button = match rest.split_once("Seat #").and_then(|(_, seat)| {
+                seat.split_whitespace()
+                    .next()
+                    .and_then(|seat| seat.parse().ok())
+            }) {
+                Some(seat) => seat,
+                None => return Err(malformed(number, line)),
+            };
+        } else if line.starts_with("Seat ") && line.contains(" in chips") {
+            history.players.push(parse_seat_line(number, line)?);
+        } else if let Some(rest) = line.strip_prefix("Dealt to ") {
+            let name = match rest.rfind(" [") {
+                Some(end) => &rest[..end],
+                None => return Err(malformed(number, line)),
+            };
+            let cards = parse_bracketed_cards(number, line, false)?;
+            get_player(number, &mut history.players, name)?.hole_cards = Some(cards);
+        } else if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = match rest.split_once(") returned to ") {
+                Some(parts) => parts,
+                None => return Err(malformed(number, line)),
+            };
+            history.actions.push(HistoryAction {
+                street,
+                player: name.to_string(),
+                kind: ActionKind::UncalledBetReturned(parse_amount(number, amount)?),
+                all_in: false,
+            });
+        } else if let Some((name, amount)) = parse_collected(line) {
+            let amount = parse_amount(number, amount)?;
+            match history
+                .winnings
+                .iter_mut()
+                .find(|(winner, _)| winner == name)
+            {
+                Some((_, total)) => *total += amount,
+                None => history.winnings.push((name.to_string(), amount)),
+            }
+        } else if let Some(player) = history
+            .players
+            .iter()
+            .position(|player| line.starts_with(&format!("{}: ", player.name)))
+        {
+            let name = history.players[player].name.clone();
+            let verb = &line[name.len() + 2..];
+            parse_player_line(number, verb, street, &name, &mut history)?;
+        }
+        // Anything else is chatter, e.g. players joining or timing out.
+    }
+
+    for player in history.players.iter_mut() {
+        if player.seat == history.button {
+            player.position = Some(Position::Button);
+        }
+    }
+
+    Ok(history)
+}
+
+fn parse_seat_line(number: usize, line: &str) -> Result<HistoryPlayer, HistoryError> {
+    let malformed = || HistoryError::new(number, HistoryErrorKind::Malformed(line.to_string()));
+
+    let (seat, rest) = match line["Seat ".len()..].split_once(": ") {
+        Some(parts) => parts,
+        None => return Err(malformed()),
+    };
+    let seat: usize = match seat.parse() {
+        Ok(seat) => seat,
+        Err(_) => return Err(malformed()),
+    };
+
+    let chips_end = match rest.find(" in chips") {
+        Some(end) => end,
+        None => return Err(malformed()),
+    };
+    let open = match rest[..chips_end].rfind(" (") {
+        Some(open) => open,
+        None => return Err(malformed()),
+    };
+
+    Ok(HistoryPlayer {
+        seat,
+        name: rest[..open].to_string(),
+        stack: parse_amount(number, &rest[open + 2..chips_end])?,
+        position: None,
+        hole_cards: None,
+        sitting_out: rest.ends_with("is sitting out"),
+    })
+}
+
+fn parse_player_line(
+    number: usize,
+    verb: &str,
+    street: Street,
+    name: &str,
+    history: &mut HandHistory,
+) -> Result<(), HistoryError> {
+    let all_in = verb.ends_with("and is all-in");
+    let verb = verb.trim_end_matches("and is all-in").trim_end();
+    let amount = |text: &str| parse_amount(number, text);
+
+    let kind = if verb == "folds" {
+        ActionKind::Fold
+    } else if verb == "checks" {
+        ActionKind::Check
+    } else if let Some(rest) = verb.strip_prefix("calls ") {
+        ActionKind::Call(amount(rest)?)
+    } else if let Some(rest) = verb.strip_prefix("bets ") {
+        ActionKind::Bet(amount(rest)?)
+    } else if let Some(rest) = verb.strip_prefix("raises ") {
+        match rest.split_once(" to ") {
+            Some((by, to)) => ActionKind::Raise {
+                by: amount(by)?,
+                to: amount(to)?,
+            },
+            None => {
+                return Err(HistoryError::new(
+                    number,
+                    HistoryErrorKind::Malformed(verb.to_string()),
+                ))
+            }
+        }
+    } else if let Some(rest) = verb.strip_prefix("posts small & big blinds ") {
+        ActionKind::PostDeadBlinds(amount(rest)?)
+    } else if let Some(rest) = verb.strip_prefix("posts small blind ") {
+        get_player(number, &mut history.players, name)?.position = Some(Position::SmallBlind);
+        ActionKind::PostSmallBlind(amount(rest)?)
+    } else if let Some(rest) = verb.strip_prefix("posts big blind ") {
+        get_player(number, &mut history.players, name)?.position = Some(Position::BigBlind);
+        ActionKind::PostBigBlind(amount(rest)?)
+    } else if let Some(rest) = verb.strip_prefix("posts the ante ") {
+        ActionKind::PostAnte(amount(rest)?)
+    } else if verb.starts_with("shows [") {
+        let cards = parse_bracketed_cards(number, verb, false)?;
+        get_player(number, &mut history.players, name)?.hole_cards = Some(cards);
+        return Ok(());
+    } else {
+        // Mucks, sit outs and other moves that do not change the pot.
+        return Ok(());
+    };
+
+    history.actions.push(HistoryAction {
+        street,
+        player: name.to_string(),
+        kind,
+        all_in,
+    });
+
+    Ok(())
+}
+
+fn parse_summary_line(
+    number: usize,
+    line: &str,
+    history: &mut HandHistory,
+) -> Result<(), HistoryError> {
+    if line.starts_with("Board [") {
+        history.board = parse_bracketed_cards(number, line, false)?;
+    } else if let Some(rest) = line.strip_prefix("Total pot ") {
+        let mut parts = rest.split(" | ");
+        let pot = parts.next().unwrap_or("");
+        history.total_pot = Some(parse_amount(
+            number,
+            pot.split_whitespace().next().unwrap_or(""),
+        )?);
+
+        for part in parts {
+            if let Some(rake) = part.strip_prefix("Rake ") {
+                history.rake = Some(parse_amount(number, rake)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// "name collected $1.30 from pot", also from the main and side pots.
+fn parse_collected(line: &str) -> Option<(&str, &str)> {
+    let (name, rest) = line.split_once(" collected ")?;
+    let (amount, _) = rest.split_once(" from ")?;
+
+    Some((name, amount))
+}
+
+fn get_player<'a>(
+    number: usize,
+    players: &'a mut [HistoryPlayer],
+    name: &str,
+) -> Result<&'a mut HistoryPlayer, HistoryError> {
+    match players.iter_mut().find(|player| player.name == name) {
+        Some(player) => Ok(player),
+        None => Err(HistoryError::new(
+            number,
+            HistoryErrorKind::UnknownPlayer(name.to_string()),
+        )),
+    }
+}
+
+// Cards between square brackets, from the first or the last pair of
+// brackets on the line.
+fn parse_bracketed_cards(number: usize, line: &str, last: bool) -> Result<Vec<Card>, HistoryError> {
+    let open = if last {
+        line.rfind('[')
+    } else {
+        line.find('[')
+    };
+    let cards = open.and_then(|open| {
+        let rest = &line[open + 1..];
+        rest.find(']').map(|close| &rest[..close])
+    });
+
+    let cards = match cards {
+        Some(cards) => cards,
+        None => {
+            return Err(HistoryError::new(
+                number,
+                HistoryErrorKind::Malformed(line.to_string()),
+            ))
+        }
+    };
+
+    if last {
+        // Streets list the earlier board first, e.g. "[2c 7h Qs] [Jd]".
+        let mut board = match line.find('[') {
+            Some(first) if Some(first) != open => parse_bracketed_cards(number, line, false)?,
+            _ => Vec::new(),
+        };
+        board.extend(parse_cards(number, cards)?);
+        return Ok(board);
+    }
+
+    parse_cards(number, cards)
+}
+
+// PokerStars writes suits in lower case, e.g. "Ah Td".
+fn parse_cards(number: usize, cards: &str) -> Result<Vec<Card>, HistoryError> {
+    cards
+        .split_whitespace()
+        .enumerate()
+        .map(|(position, card)| {
+            card.to_uppercase().parse::<Card>().map_err(|mut err| {
+                err.token = card.to_string();
+                err.position = position;
+                HistoryError::new(number, HistoryErrorKind::InvalidCard(err))
+            })
+        })
+        .collect()
+}
+
+// Reads "$1,234.56", "€0.02" or "1500" as hundredths.
+fn parse_amount(number: usize, text: &str) -> Result<u64, HistoryError> {
+    let invalid = || HistoryError::new(number, HistoryErrorKind::InvalidAmount(text.to_string()));
+
+    let digits: String = text
+        .trim()
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) if fraction.len() <= 2 => (whole, fraction),
+        Some(_) => return Err(invalid()),
+        None => (digits.as_str(), ""),
+    };
+
+    let whole: u64 = match whole.parse() {
+        Ok(whole) => whole,
+        Err(_) => return Err(invalid()),
+    };
+    let fraction: u64 = match format!("{:0<2}", fraction).parse() {
+        Ok(fraction) => fraction,
+        Err(_) => return Err(invalid()),
+    };
+
+    Ok(whole * 100 + fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::{parse_hand, table::TableConfig};
+
+    // Alice is all-in for the main pot, Bob calls all-in for a side pot and
+    // Carol gets the rest of her raise back.
+    const SIDE_POTS: &str = "\
+PokerStars Hand #1001:  Hold'em No Limit ($0.10/$0.20 USD) - 2024/01/31 20:15:00 ET
+Table 'Replay' 3-max Seat #1 is the button
+Seat 1: Alice ($5.00 in chips)
+Seat 2: Bob ($15.00 in chips)
+Seat 3: Carol ($30.00 in chips)
+Bob: posts small blind $0.10
+Carol: posts big blind $0.20
+*** HOLE CARDS ***
+Dealt to Alice [Ah 2d]
+Dealt to Bob [4s Qs]
+Dealt to Carol [4c 3d]
+Alice: raises $4.80 to $5.00 and is all-in
+Bob: calls $4.90
+Carol: raises $25.00 to $30.00 and is all-in
+Bob: calls $10.00 and is all-in
+Uncalled bet ($15.00) returned to Carol
+*** FLOP *** [Qh Ts Jd]
+*** TURN *** [Qh Ts Jd] [Td]
+*** RIVER *** [Qh Ts Jd Td] [Ad]
+*** SHOW DOWN ***
+Alice: shows [Ah 2d]
+Bob: shows [4s Qs]
+Carol: shows [4c 3d]
+Alice collected $15.00 from pot
+Bob collected $20.00 from pot
+*** SUMMARY ***
+Total pot $35.00 Main pot $15.00. Side pot-1 $20.00. | Rake $0
+Board [Qh Ts Jd Td Ad]
+Seat 1: Alice (button) showed [Ah 2d] and won ($15.00)
+Seat 2: Bob (small blind) showed [4s Qs] and won ($20.00)
+Seat 3: Carol (big blind) showed [4c 3d] and lost
+";
+
+    fn header() -> HistoryHeader {
+        HistoryHeader {
+            hand_id: "1001".to_string(),
+            table: "Replay".to_string(),
+            date: "2024/01/31 20:15:00 ET".to_string(),
+            players: vec!["Alice".to_string(), "Bob".to_string(), "Carol".to_string()],
+        }
+    }
+
+    fn side_pots_record() -> HandRecord {
+        HandRecord {
+            config: TableConfig {
+                small_blind: 10,
+                big_blind: 20,
+                ante: 0,
+                limit: Limit::NoLimit,
+            },
+            stacks: vec![500, 1500, 3000],
+            button: 0,
+            seed: 5,
+            actions: vec![
+                Action::Raise(500),
+                Action::Call,
+                Action::Raise(3000),
+                Action::Call,
+            ],
+        }
+    }
+
+    fn read(text: &str) -> Vec<Result<HandHistory, HistoryError>> {
+        HistoryReader::new(text.as_bytes()).collect()
+    }
+
+    // The record that plays a parsed hand, given the seed the deck was
+    // shuffled with.
+    fn to_record(history: &HandHistory, seed: u64) -> HandRecord {
+        let mut config = TableConfig {
+            small_blind: 0,
+            big_blind: 0,
+            ante: 0,
+            limit: Limit::NoLimit,
+        };
+        let mut actions = Vec::new();
+
+        for action in &history.actions {
+            match action.kind {
+                ActionKind::PostAnte(amount) => config.ante = amount,
+                ActionKind::PostSmallBlind(amount) => config.small_blind = amount,
+                ActionKind::PostBigBlind(amount) => config.big_blind = amount,
+                ActionKind::Fold => actions.push(Action::Fold),
+                ActionKind::Check => actions.push(Action::Check),
+                ActionKind::Call(_) => actions.push(Action::Call),
+                ActionKind::Bet(to) => actions.push(Action::Bet(to)),
+                ActionKind::Raise { to, .. } => actions.push(Action::Raise(to)),
+                ActionKind::PostDeadBlinds(_) | ActionKind::UncalledBetReturned(_) => {}
+            }
+        }
+
+        HandRecord {
+            config,
+            stacks: history.players.iter().map(|player| player.stack).collect(),
+            button: history.button - 1,
+            seed,
+            actions,
+        }
+    }
+
+    #[test]
+    fn reads_all_ins_side_pots_and_the_showdown() {
+        let hands = read(SIDE_POTS);
+        assert_eq!(hands.len(), 1);
+        let hand = hands[0].as_ref().unwrap();
+
+        assert_eq!(hand.hand_id, "1001");
+        assert_eq!(hand.game, "Hold'em No Limit ($0.10/$0.20 USD)");
+        assert_eq!(hand.table, "Replay");
+        assert_eq!(hand.max_seats, Some(3));
+        assert_eq!(hand.button, 1);
+
+        let stacks: Vec<u64> = hand.players.iter().map(|player| player.stack).collect();
+        assert_eq!(stacks, [500, 1500, 3000]);
+        assert_eq!(
+            hand.player("Alice").unwrap().position,
+            Some(Position::Button)
+        );
+        assert_eq!(
+            hand.player("Carol").unwrap().position,
+            Some(Position::BigBlind)
+        );
+        assert_eq!(
+            hand.player("Bob").unwrap().hole_cards,
+            Some(parse_hand("4S QS").unwrap())
+        );
+        assert_eq!(hand.board, parse_hand("QH TS JD TD AD").unwrap());
+
+        let kinds: Vec<(&str, ActionKind, bool)> = hand
+            .actions
+            .iter()
+            .map(|action| (action.player.as_str(), action.kind, action.all_in))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Bob", ActionKind::PostSmallBlind(10), false),
+                ("Carol", ActionKind::PostBigBlind(20), false),
+                ("Alice", ActionKind::Raise { by: 480, to: 500 }, true),
+                ("Bob", ActionKind::Call(490), false),
+                ("Carol", ActionKind::Raise { by: 2500, to: 3000 }, true),
+                ("Bob", ActionKind::Call(1000), true),
+                ("Carol", ActionKind::UncalledBetReturned(1500), false),
+            ]
+        );
+
+        assert_eq!(
+            hand.winnings,
+            [("Alice".to_string(), 1500), ("Bob".to_string(), 2000)]
+        );
+        assert_eq!(hand.total_pot, Some(3500));
+        assert_eq!(hand.rake, Some(0));
+        assert_eq!(hand.showdown_winners(), ["Alice"]);
+    }
+
+    #[test]
+    fn reports_the_line_and_reads_on() {
+        let text = SIDE_POTS.replace("Dealt to Bob [4s Qs]", "Dealt to Bob [4s Qx]");
+        let hands = read(&format!("{}\n{}", text, SIDE_POTS));
+
+        let err = hands[0].as_ref().unwrap_err();
+        assert_eq!(err.line(), 10);
+        assert!(matches!(err.kind(), HistoryErrorKind::InvalidCard(_)));
+
+        // The next hand starts after the blank line.
+        let next = hands[1].as_ref().unwrap();
+        assert_eq!(next.line, SIDE_POTS.lines().count() + 2);
+    }
+
+    #[test]
+    fn amounts_are_read_in_hundredths() {
+        let text = SIDE_POTS
+            .replace("($5.00 in chips)", "($1,234.5 in chips)")
+            .replace("($15.00 in chips)", "(1500 in chips)")
+            .replace("($30.00 in chips)", "(€0.07 in chips)");
+        let hand = read(&text).remove(0).unwrap();
+
+        let stacks: Vec<u64> = hand.players.iter().map(|player| player.stack).collect();
+        assert_eq!(stacks, [123450, 150000, 7]);
+
+        let text = SIDE_POTS.replace("($5.00 in chips)", "($5.001 in chips)");
+        let err = read(&text).remove(0).unwrap_err();
+        assert_eq!(err.line(), 3);
+        assert!(matches!(err.kind(), HistoryErrorKind::InvalidAmount(_)));
+    }
+
+    #[test]
+    fn writes_chips_as_cents() {
+        assert_eq!(
+            write_hand_history(&side_pots_record(), &header()).unwrap(),
+            SIDE_POTS
+        );
+    }
+
+    #[test]
+    fn written_hands_read_back_the_same() {
+        let hand = read(SIDE_POTS).remove(0).unwrap();
+        let record = to_record(&hand, 5);
+        assert_eq!(record, side_pots_record());
+
+        let written = write_hand_history(&record, &header()).unwrap();
+        assert_eq!(read(&written).remove(0).unwrap(), hand);
+    }
+}
@@ -0,0 +1,287 @@
+use super::{
+    pots::Pot,
+    seat::{self, return_uncalled_bet, Seat},
+    table::{Action, ActionError, LegalActions},
+    HandRank,
+};
+
+/// Stakes of a fixed-limit game such as five card draw or seven card stud.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LimitConfig {
+    pub ante: u64,
+    /// Forced opening bet of the lowest up-card in stud, unused in draw.
+    pub bring_in: u64,
+    /// Bet size of the early rounds.
+    pub small_bet: u64,
+    /// Bet size of the late rounds.
+    pub big_bet: u64,
+    /// Bets and raises allowed in one round, usually 4.
+    pub max_bets: u32,
+}
+
+/// Fixed-limit betting for games that deal their own cards. Every bet and
+/// raise is exactly one bet of the round's size, up to a cap of bets per
+/// round. The game decides who opens each round and when the hand moves on.
+#[derive(Debug, Clone)]
+pub struct LimitBetting {
+    seats: Vec<Seat>,
+    max_bets: u32,
+    bet_size: u64,
+    current_bet: u64,
+    bets: u32,
+    to_act: Option<usize>,
+    pots: Vec<Pot>,
+    payouts: Vec<u64>,
+}
+
+impl LimitBetting {
+    pub fn new(stacks: &[u64], max_bets: u32) -> Self {
+        LimitBetting {
+            seats: stacks.iter().map(|stack| Seat::new(*stack)).collect(),
+            max_bets,
+            bet_size: 0,
+            current_bet: 0,
+            bets: 0,
+            to_act: None,
+            pots: Vec::new(),
+            payouts: vec![0; stacks.len()],
+        }
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    /// The main and side pots of the last hand, once it is over.
+    pub fn pots(&self) -> &[Pot] {
+        &self.pots
+    }
+
+    /// Chips each seat won at the end of the last hand.
+    pub fn payouts(&self) -> &[u64] {
+        &self.payouts
+    }
+
+    /// Every chip put in the pot during the current hand.
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|seat| seat.contributed).sum()
+    }
+
+    /// Number of seats still holding cards.
+    pub fn live_count(&self) -> usize {
+        self.seats.iter().filter(|seat| seat.is_live()).count()
+    }
+
+    /// Deals every seat with chips into a new hand.
+    pub fn start_hand(&mut self) {
+        for seat in self.seats.iter_mut() {
+            seat.start_hand();
+        }
+        self.to_act = None;
+        self.pots.clear();
+        self.payouts = vec![0; self.seats.len()];
+    }
+
+    /// Takes an ante, which goes in the pot without counting as a bet.
+    pub fn post_ante(&mut self, seat: usize, amount: u64) -> u64 {
+        self.seats[seat].post_ante(amount)
+    }
+
+    /// Opens a betting round of the given bet size with the first seat from
+    /// `first` on, going left, that can act.
+    pub fn start_round(&mut self, bet_size: u64, first: usize) {
+        for seat in self.seats.iter_mut() {
+            seat.street_bet = 0;
+            seat.acted = false;
+        }
+        self.bet_size = bet_size;
+        self.current_bet = 0;
+        self.bets = 0;
+
+        self.to_act = if self.seats.iter().filter(|seat| seat.can_act()).count() >= 2 {
+            self.seat_from(first, Seat::can_act)
+        } else {
+            None
+        };
+    }
+
+    /// Posts the stud bring-in, a forced bet below the bet size that the
+    /// next players may call or complete to a full bet.
+    pub fn post_bring_in(&mut self, seat: usize, amount: u64) -> u64 {
+        let posted = self.seats[seat].put_in(amount);
+        self.seats[seat].acted = true;
+        self.current_bet = self.current_bet.max(posted);
+
+        if self.to_act == Some(seat) {
+            self.to_act = self.next_to_act(seat);
+        }
+
+        posted
+    }
+
+    /// What the player to act may do. Bets and raises have a single legal
+    /// amount, given as both `min_raise_to` and `max_raise_to`.
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        let index = self.to_act?;
+        let seat = &self.seats[index];
+        let to_call = (self.current_bet - seat.street_bet).min(seat.stack);
+
+        let others_can_act = self
+            .seats
+            .iter()
+            .enumerate()
+            .any(|(i, other)| i != index && other.can_act());
+
+        if self.bets >= self.max_bets || !others_can_act || seat.stack <= to_call {
+            return Some(LegalActions {
+                to_call,
+                min_raise_to: None,
+                max_raise_to: None,
+            });
+        }
+
+        // Raising a bring-in completes it to a full bet.
+        let raise_to = if self.current_bet < self.bet_size {
+            self.bet_size
+        } else {
+            self.current_bet + self.bet_size
+        }
+        .min(seat.street_bet + seat.stack);
+
+        Some(LegalActions {
+            to_call,
+            min_raise_to: Some(raise_to),
+            max_raise_to: Some(raise_to),
+        })
+    }
+
+    /// Applies an action for the player to act. The round is over once
+    /// nobody is left to act.
+    pub fn act(&mut self, action: Action) -> Result<(), ActionError> {
+        let (index, legal) = match (self.to_act, self.legal_actions()) {
+            (Some(index), Some(legal)) => (index, legal),
+            _ => return Err(ActionError::WrongPhase),
+        };
+
+        match action {
+            Action::Fold => self.seats[index].folded = true,
+            Action::Check => {
+                if !legal.can_check() {
+                    return Err(ActionError::CannotCheck {
+                        to_call: legal.to_call,
+                    });
+                }
+            }
+            Action::Call => {
+                if legal.can_check() {
+                    return Err(ActionError::NothingToCall);
+                }
+                self.seats[index].put_in(legal.to_call);
+            }
+            Action::Bet(amount) | Action::Raise(amount) => {
+                match (action, self.current_bet) {
+                    (Action::Bet(_), bet) if bet > 0 => return Err(ActionError::BetAlreadyMade),
+                    (Action::Raise(_), 0) => return Err(ActionError::NoBetToRaise),
+                    _ => {}
+                }
+
+                let raise_to = match legal.min_raise_to {
+                    Some(raise_to) => raise_to,
+                    None => return Err(ActionError::RaiseNotAllowed),
+                };
+                if amount != raise_to {
+                    return Err(ActionError::InvalidAmount {
+                        amount,
+                        min: raise_to,
+                        max: raise_to,
+                    });
+                }
+
+                let seat = &mut self.seats[index];
+                seat.put_in(amount - seat.street_bet);
+
+                // A short all-in raise still needs an answer but does not
+                // use up one of the bets of the round.
+                let full = if self.current_bet < self.bet_size {
+                    self.bet_size
+                } else {
+                    self.current_bet + self.bet_size
+                };
+                if amount >= full {
+                    self.bets += 1;
+                }
+                self.current_bet = amount;
+
+                for (i, other) in self.seats.iter_mut().enumerate() {
+                    if i != index {
+                        other.acted = false;
+                    }
+                }
+            }
+        }
+
+        self.seats[index].acted = true;
+        self.to_act = self.next_to_act(index);
+
+        Ok(())
+    }
+
+    /// Gives back the part of the largest bet of the round that nobody else
+    /// matched.
+    pub fn return_uncalled_bet(&mut self) {
+        return_uncalled_bet(&mut self.seats);
+    }
+
+    /// Ends the hand and pays the winners: the last live seat takes the pot
+    /// uncontested, otherwise the pots go to the best of the given hand
+    /// ranks, one per seat. Odd chips go left of the button.
+    pub fn finish_hand(&mut self, hand_ranks: &[Option<HandRank>], button: usize) {
+        let settlement = seat::finish_hand(&mut self.seats, hand_ranks, button);
+
+        self.pots = settlement.pots;
+        self.payouts = settlement.payouts;
+        self.to_act = None;
+    }
+
+    /// First seat after `from`, going left, that still has chips.
+    pub fn next_with_chips(&self, from: usize) -> usize {
+        seat::next_seat(&self.seats, from, |seat| seat.stack > 0)
+    }
+
+    // Next seat after `from` that still has to act, or `None` once the round
+    // is over.
+    fn next_to_act(&self, from: usize) -> Option<usize> {
+        if self.live_count() < 2 {
+            return None;
+        }
+
+        let current_bet = self.current_bet;
+        let seat = self.seat_from(from + 1, |seat| {
+            seat.can_act() && (!seat.acted || seat.street_bet < current_bet)
+        })?;
+
+        // Alone with chips and nothing to call, there is nobody to bet with.
+        let others_can_act = self
+            .seats
+            .iter()
+            .enumerate()
+            .any(|(i, other)| i != seat && other.can_act());
+        if !others_can_act && self.seats[seat].street_bet >= current_bet {
+            return None;
+        }
+
+        Some(seat)
+    }
+
+    // First seat from `from` on, going left and wrapping around, that matches
+    // the predicate.
+    fn seat_from<F: Fn(&Seat) -> bool>(&self, from: usize, predicate: F) -> Option<usize> {
+        (0..self.seats.len())
+            .map(|offset| (from + offset) % self.seats.len())
+            .find(|seat| predicate(&self.seats[*seat]))
+    }
+}
@@ -0,0 +1,158 @@
+use super::{Card, ParseCardError, ParseCardErrorKind, Rank, Suit};
+
+/// Ways of writing a card out. Every notation parses back into the same card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CardNotation {
+    /// Rank and suit letter, e.g. `10H`, the way cards display.
+    #[default]
+    Standard,
+    /// One character per rank and a lowercase suit, e.g. `Th`, as in ranges
+    /// and hand histories.
+    Compact,
+    /// Rank and suit symbol, e.g. `10♥`.
+    Symbol,
+    /// A character of the Playing Cards Unicode block, e.g. `🂺`.
+    Unicode,
+    /// Rank and suit names, e.g. `Ten of Hearts`.
+    Long,
+}
+
+impl Card {
+    pub fn to_notation(self, notation: CardNotation) -> String {
+        match notation {
+            CardNotation::Standard => self.to_string(),
+            CardNotation::Compact => format!(
+                "{}{}",
+                self.rank.short_symbol(),
+                self.suit.symbol().to_lowercase()
+            ),
+            CardNotation::Symbol => format!("{}{}", self.rank, self.suit.glyph()),
+            CardNotation::Unicode => get_playing_card(self).to_string(),
+            CardNotation::Long => format!("{} of {}", self.rank.name(), self.suit.name()),
+        }
+    }
+}
+
+/// Writes the cards in the given notation, joined by the separator, e.g.
+/// `A♥, K♠` with [`CardNotation::Symbol`] and `", "`.
+pub fn format_cards(cards: &[Card], notation: CardNotation, separator: &str) -> String {
+    cards
+        .iter()
+        .map(|card| card.to_notation(notation))
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+// The code point of the card in the Playing Cards block, where the ranks of
+// each suit run from the ace at 1 to the king at 0xE, skipping the knight.
+fn get_playing_card(card: Card) -> char {
+    let suit = match card.suit {
+        Suit::Spades => 0x1F0A0,
+        Suit::Hearts => 0x1F0B0,
+        Suit::Diamonds => 0x1F0C0,
+        Suit::Clubs => 0x1F0D0,
+    };
+    let rank = match card.rank {
+        Rank::Ace => 0x1,
+        Rank::Queen => 0xD,
+        Rank::King => 0xE,
+        rank => rank.value() as u32,
+    };
+
+    char::from_u32(suit + rank).expect("playing cards are valid code points")
+}
+
+// A card from the Playing Cards Unicode block, e.g. U+1F0B1 for the ace of
+// hearts. Knights, card backs and jokers are not cards of the deck.
+pub(super) fn parse_playing_card(symbol: char, token: &str) -> Result<Card, ParseCardError> {
+    let suit = match symbol as u32 & !0xF {
+        0x1F0A0 => Suit::Spades,
+        0x1F0B0 => Suit::Hearts,
+        0x1F0C0 => Suit::Diamonds,
+        0x1F0D0 => Suit::Clubs,
+        _ => return Err(ParseCardError::new(token, ParseCardErrorKind::Malformed)),
+    };
+    let rank = match symbol as u32 & 0xF {
+        0x1 => Rank::Ace,
+        0xB => Rank::Jack,
+        0xD => Rank::Queen,
+        0xE => Rank::King,
+        value @ 0x2..=0xA => Rank::from_value(value as u8).unwrap(),
+        _ => return Err(ParseCardError::new(token, ParseCardErrorKind::InvalidRank)),
+    };
+
+    Ok(Card::new(rank, suit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::parse_hand;
+
+    fn card(card: &str) -> Result<Card, ParseCardErrorKind> {
+        card.parse::<Card>().map_err(|err| err.kind())
+    }
+
+    #[test]
+    fn every_notation_parses_back() {
+        let notations = [
+            CardNotation::Standard,
+            CardNotation::Compact,
+            CardNotation::Symbol,
+            CardNotation::Unicode,
+            CardNotation::Long,
+        ];
+
+        for suit in Suit::ALL {
+            for rank in Rank::ALL {
+                let card = Card::new(rank, suit);
+                for notation in notations {
+                    assert_eq!(card.to_notation(notation).parse::<Card>(), Ok(card));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_symbols_unicode_cards_and_long_forms() {
+        let queen = Card::new(Rank::Queen, Suit::Spades);
+
+        assert_eq!(card("Q♠"), Ok(queen));
+        assert_eq!(card("q♤"), Ok(queen));
+        assert_eq!(card("🂭"), Ok(queen));
+        assert_eq!(card("Queen of Spades"), Ok(queen));
+        assert_eq!(card("queen of spade"), Ok(queen));
+        assert_eq!(card("10♡"), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!(card("🃑"), Ok(Card::new(Rank::Ace, Suit::Clubs)));
+    }
+
+    #[test]
+    fn parses_hands_separated_by_commas() {
+        assert_eq!(
+            parse_hand("A♥, 🂮,Queen of Clubs  10d"),
+            Ok(vec![
+                Card::new(Rank::Ace, Suit::Hearts),
+                Card::new(Rank::King, Suit::Spades),
+                Card::new(Rank::Queen, Suit::Clubs),
+                Card::new(Rank::Ten, Suit::Diamonds),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_cards_outside_the_deck() {
+        // The knight of spades, a card back and a joker.
+        assert_eq!(card("🂬"), Err(ParseCardErrorKind::InvalidRank));
+        assert_eq!(card("🂠"), Err(ParseCardErrorKind::InvalidRank));
+        assert_eq!(card("🃟"), Err(ParseCardErrorKind::InvalidRank));
+        assert_eq!(card("♥"), Err(ParseCardErrorKind::Malformed));
+
+        assert_eq!(card("X♠"), Err(ParseCardErrorKind::InvalidRank));
+        assert_eq!(card("A☆"), Err(ParseCardErrorKind::InvalidSuit));
+        assert_eq!(card("Ace of Stars"), Err(ParseCardErrorKind::InvalidSuit));
+        assert_eq!(card("Ace from Spades"), Err(ParseCardErrorKind::Malformed));
+
+        let err = parse_hand("A♥, K♠, Q★").unwrap_err();
+        assert_eq!((err.token(), err.position()), ("Q★", 2));
+    }
+}
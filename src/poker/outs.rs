@@ -0,0 +1,297 @@
+use std::{cmp::Reverse, collections::HashSet, error::Error, fmt};
+
+use super::{
+    best_hand,
+    fast::{evaluate_mask, CardMask},
+    for_each_combination, get_hand_rank, Card, HandRank, HandType, Rank, RankingRules, Suit,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutsError {
+    /// Outs are counted on the flop or the turn, i.e. with three or four
+    /// board cards.
+    InvalidBoard(usize),
+    DuplicateCard(Card),
+}
+
+impl fmt::Display for OutsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutsError::InvalidBoard(len) => {
+                write!(f, "the board has {} cards, outs need 3 or 4", len)
+            }
+            OutsError::DuplicateCard(card) => write!(f, "the card {} is used twice", card),
+        }
+    }
+}
+
+impl Error for OutsError {}
+
+/// An unseen card that improves the hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Out {
+    pub card: Card,
+    /// The best hand once the card is dealt.
+    pub rank: HandRank,
+    /// Categories the card completes, the best first. A card can complete a
+    /// straight and a flush at once, which is where outs overlap.
+    pub hand_types: Vec<HandType>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutsReport {
+    /// The best hand before the next card.
+    pub current: HandRank,
+    /// Every out, in deck order.
+    pub outs: Vec<Out>,
+    /// Number of outs completing each category, strongest category first.
+    /// An out completing several categories counts in each of them.
+    pub by_hand_type: Vec<(HandType, usize)>,
+    /// Number of outs counted in more than one category.
+    pub overlap: usize,
+    /// Cards that are neither held, on the board nor in the opponent's hand.
+    pub unseen: usize,
+    /// Chance that the next card is an out, in percent.
+    pub next_card: f64,
+    /// Chance of having improved by the river, in percent. On the flop every
+    /// turn and river pair is dealt, so runner-runner draws count too.
+    pub by_river: f64,
+}
+
+/// Lists the outs of a Hold'em hand on the flop or the turn.
+///
+/// Without an opponent an out is a card that improves the hand to a better
+/// category than both the current hand and the board once the card is dealt,
+/// so a card that only pairs the board is no out. Given the opponent's hole
+/// cards, an out is a card after which a hand that is behind or tied beats
+/// the opponent's, whatever the category; a hand already ahead has none.
+pub fn outs(
+    hole_cards: &[Card; 2],
+    board: &[Card],
+    opponent: Option<&[Card; 2]>,
+) -> Result<OutsReport, OutsError> {
+    if board.len() != 3 && board.len() != 4 {
+        return Err(OutsError::InvalidBoard(board.len()));
+    }
+
+    let mut known: HashSet<Card> = HashSet::new();
+    for card in hole_cards
+        .iter()
+        .chain(board)
+        .chain(opponent.into_iter().flatten())
+    {
+        if !known.insert(*card) {
+            return Err(OutsError::DuplicateCard(*card));
+        }
+    }
+
+    let unseen: Vec<Card> = Suit::ALL
+        .iter()
+        .flat_map(|suit| Rank::ALL.iter().map(|rank| Card::new(*rank, *suit)))
+        .filter(|card| !known.contains(card))
+        .collect();
+
+    let mut cards = hole_cards.to_vec();
+    cards.extend_from_slice(board);
+    let current = best_hand(&cards).0;
+
+    let opponent_cards: Option<Vec<Card>> = opponent.map(|opponent| {
+        let mut cards = opponent.to_vec();
+        cards.extend_from_slice(board);
+        cards
+    });
+    let ahead = opponent_cards
+        .as_ref()
+        .is_some_and(|opponent| current > best_hand(opponent).0);
+
+    let mut outs: Vec<Out> = Vec::new();
+
+    for card in &unseen {
+        let mut hand = cards.clone();
+        hand.push(*card);
+        let rank = best_hand(&hand).0;
+
+        let is_out = match &opponent_cards {
+            Some(opponent) => {
+                let mut opponent = opponent.clone();
+                opponent.push(*card);
+                !ahead && rank > best_hand(&opponent).0
+            }
+            None => {
+                let mut board = board.to_vec();
+                board.push(*card);
+                let board_type = get_hand_rank(RankingRules::High, &board).hand_type;
+
+                rank.hand_type > current.hand_type && rank.hand_type > board_type
+            }
+        };
+
+        if is_out {
+            let hand_types = get_completed_types(&hand, current.hand_type, rank.hand_type);
+            outs.push(Out {
+                card: *card,
+                rank,
+                hand_types,
+            });
+        }
+    }
+
+    let mut by_hand_type: Vec<(HandType, usize)> = Vec::new();
+    for out in &outs {
+        for hand_type in &out.hand_types {
+            match by_hand_type
+                .iter_mut()
+                .find(|(other, _)| other == hand_type)
+            {
+                Some((_, count)) => *count += 1,
+                None => by_hand_type.push((*hand_type, 1)),
+            }
+        }
+    }
+    by_hand_type.sort_unstable_by_key(|(hand_type, _)| Reverse(*hand_type));
+
+    let next_card = outs.len() as f64 * 100.0 / unseen.len() as f64;
+    let by_river = match board.len() {
+        3 if !ahead => get_river_chance(
+            &cards,
+            board,
+            current.hand_type,
+            opponent_cards.as_deref(),
+            &unseen,
+        ),
+        _ => next_card,
+    };
+
+    Ok(OutsReport {
+        current,
+        overlap: outs.iter().filter(|out| out.hand_types.len() > 1).count(),
+        outs,
+        by_hand_type,
+        unseen: unseen.len(),
+        next_card,
+        by_river,
+    })
+}
+
+// The best category the last card makes, along with any straight or flush
+// it completes below that, as long as it beats the current category.
+fn get_completed_types(hand: &[Card], current: HandType, best: HandType) -> Vec<HandType> {
+    let mut hand_types = vec![best];
+    let last = hand.len() - 1;
+
+    for_each_combination(last, 4, |indexes| {
+        let mut five: Vec<Card> = indexes.iter().map(|i| hand[*i]).collect();
+        five.push(hand[last]);
+        let hand_type = get_hand_rank(RankingRules::High, &five).hand_type;
+
+        if matches!(hand_type, HandType::Straight | HandType::Flush)
+            && hand_type > current
+            && !hand_types.contains(&hand_type)
+        {
+            hand_types.push(hand_type);
+        }
+    });
+
+    hand_types.sort_unstable_by_key(|hand_type| Reverse(*hand_type));
+    hand_types
+}
+
+// Share of turn and river pairs after which the hand has improved past the
+// board, or beats the opponent's.
+fn get_river_chance(
+    cards: &[Card],
+    board: &[Card],
+    current: HandType,
+    opponent: Option<&[Card]>,
+    unseen: &[Card],
+) -> f64 {
+    let hand = CardMask::from_cards(cards);
+    let board = CardMask::from_cards(board);
+    let opponent = opponent.map(CardMask::from_cards);
+
+    let mut hits = 0u64;
+    let mut total = 0u64;
+
+    for_each_combination(unseen.len(), 2, |indexes| {
+        let runout = CardMask::from_cards(&[unseen[indexes[0]], unseen[indexes[1]]]);
+        let value = evaluate_mask(hand | runout);
+
+        let hit = match opponent {
+            Some(opponent) => value > evaluate_mask(opponent | runout),
+            None => {
+                let board = evaluate_mask(board | runout).hand_type();
+                value.hand_type() > current && value.hand_type() > board
+            }
+        };
+        hits += u64::from(hit);
+        total += 1;
+    });
+
+    hits as f64 * 100.0 / total.max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::parse_hand;
+
+    fn hole_cards(hand: &str) -> [Card; 2] {
+        let cards = parse_hand(hand).unwrap();
+        [cards[0], cards[1]]
+    }
+
+    #[test]
+    fn flush_and_straight_draw() {
+        let board = parse_hand("7H 6C 2H").unwrap();
+        let report = outs(&hole_cards("9H 8H"), &board, None).unwrap();
+
+        // Pairing a hole card improves on nine high too.
+        assert_eq!(
+            report.by_hand_type,
+            vec![
+                (HandType::Flush, 9),
+                (HandType::Straight, 8),
+                (HandType::OnePair, 6)
+            ]
+        );
+        assert_eq!(report.overlap, 2);
+        let draws = report
+            .outs
+            .iter()
+            .filter(|out| out.rank.hand_type >= HandType::Straight)
+            .count();
+        assert_eq!(draws, 15);
+        assert_eq!(report.outs.len(), 21);
+        assert_eq!(report.unseen, 47);
+        assert!((report.next_card - 21.0 * 100.0 / 47.0).abs() < 1e-9);
+        assert!(report.by_river > report.next_card);
+    }
+
+    #[test]
+    fn pairing_the_board_is_no_out() {
+        let board = parse_hand("7H 7C 2H").unwrap();
+        let report = outs(&hole_cards("AS KD"), &board, None).unwrap();
+
+        let mut cards: Vec<Card> = report.outs.iter().map(|out| out.card).collect();
+        cards.sort_unstable();
+        assert_eq!(cards, parse_hand("KC KH KS AC AD AH").unwrap());
+        assert_eq!(report.by_hand_type, vec![(HandType::TwoPair, 6)]);
+    }
+
+    #[test]
+    fn outs_against_an_opponent_need_to_be_behind() {
+        let board = parse_hand("7H 7C 2H").unwrap();
+        let (overcards, queens) = (hole_cards("AS KD"), hole_cards("QS QD"));
+
+        let behind = outs(&overcards, &board, Some(&queens)).unwrap();
+        assert_eq!(behind.outs.len(), 6);
+        assert!(behind
+            .outs
+            .iter()
+            .all(|out| matches!(out.card.rank, Rank::Ace | Rank::King)));
+
+        let ahead = outs(&queens, &board, Some(&overcards)).unwrap();
+        assert!(ahead.outs.is_empty());
+        assert_eq!(ahead.by_river, 0.0);
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Ordering;
+
+use super::{HandRank, RankingRules};
+
+/// Decides who gets the chips left over when a pot does not split evenly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OddChipRule {
+    /// One chip each to the tied winners closest to the left of the button.
+    LeftOfButton(usize),
+    /// One chip each to the tied winners in the lowest seats.
+    LowestSeat,
+}
+
+impl OddChipRule {
+    // Seats in the order they receive odd chips.
+    fn order(self, seats: usize) -> Vec<usize> {
+        match self {
+            OddChipRule::LeftOfButton(button) => (1..=seats)
+                .map(|offset| (button + offset) % seats)
+                .collect(),
+            OddChipRule::LowestSeat => (0..seats).collect(),
+        }
+    }
+}
+
+/// A main or side pot and the seats it was awarded to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u64,
+    pub eligible: Vec<usize>,
+    pub winners: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    /// The main pot first, followed by the side pots.
+    pub pots: Vec<Pot>,
+    /// Chips won by each seat.
+    pub payouts: Vec<u64>,
+}
+
+/// Splits the chips each seat put in into a main pot and side pots and awards
+/// every pot to the best hand among the seats that can win it, with hands
+/// compared under the given rules. A seat that folded has no hand rank, its
+/// chips stay in the pots it contributed to.
+///
+/// Chips put in above the largest contribution of a live hand go to the last
+/// pot, and when every hand has folded each seat gets its chips back.
+pub fn settle(
+    contributions: &[u64],
+    hand_ranks: &[Option<HandRank>],
+    rules: RankingRules,
+    odd_chips: OddChipRule,
+) -> Settlement {
+    assert_eq!(
+        contributions.len(),
+        hand_ranks.len(),
+        "every seat needs a contribution and a hand rank"
+    );
+
+    let mut levels: Vec<u64> = contributions
+        .iter()
+        .zip(hand_ranks)
+        .filter(|(amount, rank)| **amount > 0 && rank.is_some())
+        .map(|(amount, _)| *amount)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    if levels.is_empty() {
+        return Settlement {
+            pots: Vec::new(),
+            payouts: contributions.to_vec(),
+        };
+    }
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous = 0;
+
+    for level in &levels {
+        let amount: u64 = contributions
+            .iter()
+            .map(|amount| (*amount).min(*level) - (*amount).min(previous))
+            .sum();
+        let eligible: Vec<usize> = (0..contributions.len())
+            .filter(|seat| hand_ranks[*seat].is_some() && contributions[*seat] >= *level)
+            .collect();
+
+        pots.push(Pot {
+            amount,
+            eligible,
+            winners: Vec::new(),
+        });
+        previous = *level;
+    }
+
+    let dead: u64 = contributions
+        .iter()
+        .map(|amount| amount.saturating_sub(previous))
+        .sum();
+    pots.last_mut().unwrap().amount += dead;
+
+    let order = odd_chips.order(contributions.len());
+    let mut payouts = vec![0; contributions.len()];
+
+    for pot in pots.iter_mut() {
+        let best = pot
+            .eligible
+            .iter()
+            .filter_map(|seat| hand_ranks[*seat].as_ref())
+            .max_by(|a, b| rules.compare(a, b))
+            .unwrap();
+
+        pot.winners = order
+            .iter()
+            .copied()
+            .filter(|seat| {
+                pot.eligible.contains(seat)
+                    && hand_ranks[*seat]
+                        .as_ref()
+                        .is_some_and(|rank| rules.compare(rank, best) == Ordering::Equal)
+            })
+            .collect();
+
+        let share = pot.amount / pot.winners.len() as u64;
+        let odd = (pot.amount % pot.winners.len() as u64) as usize;
+
+        for (i, seat) in pot.winners.iter().enumerate() {
+            payouts[*seat] += share + u64::from(i < odd);
+        }
+
+        pot.winners.sort_unstable();
+    }
+
+    Settlement { pots, payouts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::parse_hand;
+
+    fn rank(rules: RankingRules, hand: &str) -> Option<HandRank> {
+        Some(rules.evaluate(&parse_hand(hand).unwrap()))
+    }
+
+    #[test]
+    fn side_pots_follow_the_ranking_rules() {
+        let rules = RankingRules::DeuceToSeven;
+        // Seat 0 is all-in for less with the best low, seat 1 beats seat 2
+        // for the side pot.
+        let hand_ranks = [
+            rank(rules, "7S 5D 4H 3C 2S"),
+            rank(rules, "8S 6D 4D 3H 2C"),
+            rank(rules, "KS KD 9H 8C 7D"),
+        ];
+
+        let settlement = settle(
+            &[100, 300, 300],
+            &hand_ranks,
+            rules,
+            OddChipRule::LowestSeat,
+        );
+
+        assert_eq!(settlement.payouts, vec![300, 400, 0]);
+        assert_eq!(settlement.pots[1].winners, vec![1]);
+    }
+
+    #[test]
+    fn short_deck_flush_beats_full_house() {
+        let rules = RankingRules::ShortDeck;
+        let hand_ranks = [rank(rules, "KS KD KH 9C 9D"), rank(rules, "AH JH 9H 8H 6H")];
+
+        let settlement = settle(&[200, 200], &hand_ranks, rules, OddChipRule::LowestSeat);
+
+        assert_eq!(settlement.payouts, vec![0, 400]);
+    }
+
+    #[test]
+    fn odd_chips_go_around_three_winners() {
+        let rules = RankingRules::High;
+        // Every live hand plays the board, seat 3 folded after posting.
+        let board = "AS KD QH JC TS";
+        let hand_ranks = [
+            rank(rules, board),
+            rank(rules, board),
+            rank(rules, board),
+            None,
+        ];
+        let contributions = [100, 100, 100, 2];
+
+        let left_of_button = settle(
+            &contributions,
+            &hand_ranks,
+            rules,
+            OddChipRule::LeftOfButton(1),
+        );
+        assert_eq!(left_of_button.payouts, vec![101, 100, 101, 0]);
+        assert_eq!(left_of_button.pots[0].winners, vec![0, 1, 2]);
+
+        let lowest_seat = settle(&contributions, &hand_ranks, rules, OddChipRule::LowestSeat);
+        assert_eq!(lowest_seat.payouts, vec![101, 101, 100, 0]);
+    }
+
+    #[test]
+    fn short_stack_wins_only_the_main_pot() {
+        let rules = RankingRules::High;
+        let hand_ranks = [
+            rank(rules, "AS AD AH 9C 2D"),
+            rank(rules, "KS KD 9H 8C 2S"),
+            rank(rules, "QS QD 9S 8D 3C"),
+        ];
+
+        let settlement = settle(&[50, 200, 200], &hand_ranks, rules, OddChipRule::LowestSeat);
+
+        assert_eq!(settlement.payouts, vec![150, 300, 0]);
+        assert_eq!(
+            settlement.pots,
+            vec![
+                Pot {
+                    amount: 150,
+                    eligible: vec![0, 1, 2],
+                    winners: vec![0],
+                },
+                Pot {
+                    amount: 300,
+                    eligible: vec![1, 2],
+                    winners: vec![1],
+                },
+            ]
+        );
+    }
+}
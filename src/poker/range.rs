@@ -0,0 +1,645 @@
+use std::{cmp::Reverse, collections::BTreeMap, error::Error, fmt, str::FromStr};
+
+use super::{
+    notation::{format_cards, CardNotation},
+    Card, ParseCardErrorKind, Rank, Suit,
+};
+
+/// Two hole cards, the higher card first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Combo([Card; 2]);
+
+impl Combo {
+    /// Panics if both cards are the same.
+    pub fn new(a: Card, b: Card) -> Self {
+        assert_ne!(a, b, "a combo needs two different cards");
+
+        if a > b {
+            Combo([a, b])
+        } else {
+            Combo([b, a])
+        }
+    }
+
+    pub fn cards(self) -> [Card; 2] {
+        self.0
+    }
+
+    pub fn is_pair(self) -> bool {
+        self.0[0].rank == self.0[1].rank
+    }
+
+    pub fn is_suited(self) -> bool {
+        self.0[0].suit == self.0[1].suit
+    }
+
+    /// Whether the combo holds any of the given cards.
+    pub fn is_blocked_by(self, cards: &[Card]) -> bool {
+        self.0.iter().any(|card| cards.contains(card))
+    }
+
+    fn class(self) -> HandClass {
+        let [high, low] = self.0;
+        let shape = if self.is_pair() {
+            Shape::Pair
+        } else if self.is_suited() {
+            Shape::Suited
+        } else {
+            Shape::Offsuit
+        };
+
+        HandClass {
+            high: high.rank,
+            low: low.rank,
+            shape,
+        }
+    }
+}
+
+/// Writes the combo the way ranges do, e.g. `AhKs`.
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_cards(&self.0, CardNotation::Compact, ""))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseRangeErrorKind {
+    Malformed,
+    InvalidRank,
+    InvalidSuit,
+    /// The two ends of a span such as `A2s-A5s` do not line up.
+    InvalidRange,
+    /// The weight after `:` is not a number between 0 and 1.
+    InvalidWeight,
+}
+
+/// Error returned when a part of a range cannot be parsed. The position is
+/// the zero-based index of the part within the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRangeError {
+    token: String,
+    position: usize,
+    kind: ParseRangeErrorKind,
+}
+
+impl ParseRangeError {
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn kind(&self) -> ParseRangeErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            ParseRangeErrorKind::Malformed => "malformed hand",
+            ParseRangeErrorKind::InvalidRank => "invalid rank in",
+            ParseRangeErrorKind::InvalidSuit => "invalid suit in",
+            ParseRangeErrorKind::InvalidRange => "mismatched ends in",
+            ParseRangeErrorKind::InvalidWeight => "invalid weight in",
+        };
+
+        write!(
+            f,
+            "{} {:?} at position {}",
+            problem, self.token, self.position
+        )
+    }
+}
+
+impl Error for ParseRangeError {}
+
+/// A hand range: a set of two card combos, each weighted by how often it is
+/// played, from 0 (never) to 1 (always).
+///
+/// Ranges parse from the usual notation, e.g. `QQ+, AKs, A2s-A5s, KQo,
+/// 76s-54s, AhKh, AQo:0.5`, where a part may end in `:weight` and single
+/// combos override the weight of the hands they belong to. They display in
+/// the same notation: runs of kickers or pairs merge into spans such as
+/// `A2s-A5s` or `QQ+`, and the hands left over merge into diagonal spans
+/// such as `76s-54s` when their cards move down together.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Range {
+    combos: BTreeMap<Combo, f64>,
+}
+
+impl Range {
+    pub fn new() -> Self {
+        Range::default()
+    }
+
+    /// Sets the weight of a combo. A weight of 0 takes it out of the range.
+    pub fn insert(&mut self, combo: Combo, weight: f64) {
+        if weight > 0.0 {
+            self.combos.insert(combo, weight.min(1.0));
+        } else {
+            self.combos.remove(&combo);
+        }
+    }
+
+    pub fn remove(&mut self, combo: Combo) {
+        self.combos.remove(&combo);
+    }
+
+    /// Weight of the combo, 0 when it is not in the range.
+    pub fn weight(&self, combo: Combo) -> f64 {
+        self.combos.get(&combo).copied().unwrap_or(0.0)
+    }
+
+    pub fn contains(&self, combo: Combo) -> bool {
+        self.combos.contains_key(&combo)
+    }
+
+    /// Number of combos in the range, whatever their weight.
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    /// Combos with their weights, from the highest combo down.
+    pub fn iter(&self) -> impl Iterator<Item = (Combo, f64)> + '_ {
+        self.combos
+            .iter()
+            .rev()
+            .map(|(combo, weight)| (*combo, *weight))
+    }
+
+    /// Number of combos counted by weight, e.g. 3 for `AA:0.5`.
+    pub fn total_weight(&self) -> f64 {
+        self.combos.values().sum()
+    }
+
+    /// The range without the combos holding any of the given cards, such as
+    /// the board or another player's hole cards.
+    pub fn without_cards(&self, dead: &[Card]) -> Range {
+        Range {
+            combos: self
+                .combos
+                .iter()
+                .filter(|(combo, _)| !combo.is_blocked_by(dead))
+                .map(|(combo, weight)| (*combo, *weight))
+                .collect(),
+        }
+    }
+}
+
+/// Parts are separated by commas or whitespace. Later parts override the
+/// weights set by earlier ones.
+impl FromStr for Range {
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Range::new();
+
+        for (position, token) in s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+            .enumerate()
+        {
+            let error = |kind| ParseRangeError {
+                token: token.to_string(),
+                position,
+                kind,
+            };
+
+            let (hands, weight) = match token.split_once(':') {
+                Some((hands, weight)) => match weight.parse::<f64>() {
+                    Ok(weight) if (0.0..=1.0).contains(&weight) => (hands, weight),
+                    _ => return Err(error(ParseRangeErrorKind::InvalidWeight)),
+                },
+                None => (token, 1.0),
+            };
+
+            for combo in parse_hands(hands).map_err(error)? {
+                range.insert(combo, weight);
+            }
+        }
+
+        Ok(range)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut classes: BTreeMap<HandClass, Vec<(Combo, f64)>> = BTreeMap::new();
+        for (combo, weight) in self.iter() {
+            classes
+                .entry(combo.class())
+                .or_default()
+                .push((combo, weight));
+        }
+
+        // Hands with every combo in at one weight are written as a whole,
+        // the rest combo by combo.
+        let mut whole: BTreeMap<HandClass, f64> = BTreeMap::new();
+        let mut single: Vec<(Combo, f64)> = Vec::new();
+
+        for (class, combos) in classes {
+            let weight = combos[0].1;
+            if combos.len() == class.combos().len()
+                && combos.iter().all(|(_, other)| *other == weight)
+            {
+                whole.insert(class, weight);
+            } else {
+                single.extend(combos);
+            }
+        }
+
+        // Suited and offsuit hands at the same weight merge, e.g. into `AK`.
+        for (class, weight) in whole.clone() {
+            if class.shape != Shape::Suited {
+                continue;
+            }
+
+            let offsuit = HandClass {
+                shape: Shape::Offsuit,
+                ..class
+            };
+            if whole.get(&offsuit) == Some(&weight) {
+                whole.remove(&class);
+                whole.remove(&offsuit);
+                whole.insert(
+                    HandClass {
+                        shape: Shape::Any,
+                        ..class
+                    },
+                    weight,
+                );
+            }
+        }
+
+        let pairs: Vec<(HandClass, f64)> = whole
+            .iter()
+            .rev()
+            .filter(|(class, _)| class.shape == Shape::Pair)
+            .map(|(class, weight)| (*class, *weight))
+            .collect();
+        let mut runs = get_runs(&pairs);
+
+        for high in Rank::ALL.iter().rev() {
+            for shape in [Shape::Any, Shape::Suited, Shape::Offsuit] {
+                let row: Vec<(HandClass, f64)> = whole
+                    .iter()
+                    .rev()
+                    .filter(|(class, _)| class.high == *high && class.shape == shape)
+                    .map(|(class, weight)| (*class, *weight))
+                    .collect();
+                runs.extend(get_runs(&row));
+            }
+        }
+
+        let mut parts: Vec<String> = merge_diagonals(runs)
+            .iter()
+            .map(|(hands, weight)| format!("{}{}", format_run(hands), format_weight(*weight)))
+            .collect();
+
+        single.sort_by_key(|(combo, _)| Reverse(*combo));
+        for (combo, weight) in single {
+            parts.push(format!("{}{}", combo, format_weight(weight)));
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Shape {
+    Pair,
+    Any,
+    Suited,
+    Offsuit,
+}
+
+// A starting hand without suits, such as `AKs` or `77`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct HandClass {
+    high: Rank,
+    low: Rank,
+    shape: Shape,
+}
+
+impl HandClass {
+    fn combos(self) -> Vec<Combo> {
+        let mut combos: Vec<Combo> = Vec::new();
+
+        for first in Suit::ALL {
+            for second in Suit::ALL {
+                let keep = match self.shape {
+                    Shape::Pair => first < second,
+                    Shape::Any => true,
+                    Shape::Suited => first == second,
+                    Shape::Offsuit => first != second,
+                };
+
+                if keep {
+                    combos.push(Combo::new(
+                        Card::new(self.high, first),
+                        Card::new(self.low, second),
+                    ));
+                }
+            }
+        }
+
+        combos
+    }
+
+    // The rank that changes along a span: the pair itself, or the kicker.
+    fn varying_rank(self) -> Rank {
+        match self.shape {
+            Shape::Pair => self.high,
+            _ => self.low,
+        }
+    }
+}
+
+impl fmt::Display for HandClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = match self.shape {
+            Shape::Pair | Shape::Any => "",
+            Shape::Suited => "s",
+            Shape::Offsuit => "o",
+        };
+
+        write!(
+            f,
+            "{}{}{}",
+            self.high.short_symbol(),
+            self.low.short_symbol(),
+            suffix
+        )
+    }
+}
+
+// One part of a range without its weight: a hand, a span, a hand with a
+// plus or a single combo.
+fn parse_hands(hands: &str) -> Result<Vec<Combo>, ParseRangeErrorKind> {
+    if let Some((from, to)) = hands.split_once('-') {
+        return parse_span(parse_class(from)?, parse_class(to)?);
+    }
+
+    if let Some(class) = hands.strip_suffix('+') {
+        let class = parse_class(class)?;
+        let ranks = match class.shape {
+            Shape::Pair => &Rank::ALL[class.high as usize - 2..],
+            _ => &Rank::ALL[class.low as usize - 2..class.high as usize - 2],
+        };
+
+        return Ok(ranks
+            .iter()
+            .flat_map(|rank| with_varying_rank(class, *rank).combos())
+            .collect());
+    }
+
+    if hands.chars().count() == 4 {
+        return parse_combo(hands).map(|combo| vec![combo]);
+    }
+
+    Ok(parse_class(hands)?.combos())
+}
+
+// Spans either keep the top card and move the kicker (`A2s-A5s`, `QQ-99`)
+// or move both cards together (`76s-54s`). Either end may come first.
+fn parse_span(from: HandClass, to: HandClass) -> Result<Vec<Combo>, ParseRangeErrorKind> {
+    if from.shape != to.shape {
+        return Err(ParseRangeErrorKind::InvalidRange);
+    }
+
+    let (low, high) = if from.varying_rank() <= to.varying_rank() {
+        (from, to)
+    } else {
+        (to, from)
+    };
+
+    let classes: Vec<HandClass> = if low.shape == Shape::Pair || low.high == high.high {
+        Rank::ALL[low.varying_rank() as usize - 2..=high.varying_rank() as usize - 2]
+            .iter()
+            .map(|rank| with_varying_rank(low, *rank))
+            .collect()
+    } else if high.high as u8 - high.low as u8 == low.high as u8 - low.low as u8 {
+        let gap = (low.high as u8 - low.low as u8) as usize;
+
+        Rank::ALL[low.low as usize - 2..=high.low as usize - 2]
+            .iter()
+            .map(|rank| HandClass {
+                high: Rank::ALL[*rank as usize - 2 + gap],
+                low: *rank,
+                shape: low.shape,
+            })
+            .collect()
+    } else {
+        return Err(ParseRangeErrorKind::InvalidRange);
+    };
+
+    Ok(classes.into_iter().flat_map(HandClass::combos).collect())
+}
+
+fn with_varying_rank(class: HandClass, rank: Rank) -> HandClass {
+    match class.shape {
+        Shape::Pair => HandClass {
+            high: rank,
+            low: rank,
+            ..class
+        },
+        _ => HandClass { low: rank, ..class },
+    }
+}
+
+// A hand such as `AKs`, `AKo`, `AK` or `77`.
+fn parse_class(hand: &str) -> Result<HandClass, ParseRangeErrorKind> {
+    let chars: Vec<char> = hand.chars().collect();
+    if chars.len() < 2 || chars.len() > 3 {
+        return Err(ParseRangeErrorKind::Malformed);
+    }
+
+    let first = parse_rank(chars[0])?;
+    let second = parse_rank(chars[1])?;
+    let (high, low) = if first >= second {
+        (first, second)
+    } else {
+        (second, first)
+    };
+
+    let shape = match (high == low, chars.get(2)) {
+        (true, None) => Shape::Pair,
+        (false, None) => Shape::Any,
+        (false, Some('s' | 'S')) => Shape::Suited,
+        (false, Some('o' | 'O')) => Shape::Offsuit,
+        _ => return Err(ParseRangeErrorKind::Malformed),
+    };
+
+    Ok(HandClass { high, low, shape })
+}
+
+// A single combo such as `AhKh`.
+fn parse_combo(hand: &str) -> Result<Combo, ParseRangeErrorKind> {
+    let chars: Vec<char> = hand.chars().collect();
+    let mut cards: Vec<Card> = Vec::new();
+
+    for card in chars.chunks(2) {
+        let card: String = card.iter().map(char::to_ascii_uppercase).collect();
+        match card.parse::<Card>() {
+            Ok(card) => cards.push(card),
+            Err(err) => {
+                return Err(match err.kind() {
+                    ParseCardErrorKind::Malformed => ParseRangeErrorKind::Malformed,
+                    ParseCardErrorKind::InvalidRank => ParseRangeErrorKind::InvalidRank,
+                    ParseCardErrorKind::InvalidSuit => ParseRangeErrorKind::InvalidSuit,
+                })
+            }
+        }
+    }
+
+    if cards[0] == cards[1] {
+        return Err(ParseRangeErrorKind::Malformed);
+    }
+
+    Ok(Combo::new(cards[0], cards[1]))
+}
+
+fn parse_rank(symbol: char) -> Result<Rank, ParseRangeErrorKind> {
+    match symbol.to_ascii_uppercase().to_string().parse::<Rank>() {
+        Ok(rank) => Ok(rank),
+        Err(_) => Err(ParseRangeErrorKind::InvalidRank),
+    }
+}
+
+// Splits hands into runs of consecutive hands at one weight, e.g. `QQ-99`
+// or `A5s-A2s`. The hands come with the varying rank going down.
+fn get_runs(hands: &[(HandClass, f64)]) -> Vec<(Vec<HandClass>, f64)> {
+    let mut runs: Vec<(Vec<HandClass>, f64)> = Vec::new();
+    let mut start = 0;
+
+    while start < hands.len() {
+        let weight = hands[start].1;
+        let mut end = start + 1;
+
+        while end < hands.len()
+            && hands[end].1 == weight
+            && hands[end - 1].0.varying_rank() as u8 == hands[end].0.varying_rank() as u8 + 1
+        {
+            end += 1;
+        }
+
+        runs.push((
+            hands[start..end].iter().map(|(hand, _)| *hand).collect(),
+            weight,
+        ));
+        start = end;
+    }
+
+    runs
+}
+
+// Chains the hands left alone by `get_runs` whose cards both move down one
+// rank at a time, e.g. `76s`, `65s` and `54s`, keeping the order of the runs.
+fn merge_diagonals(runs: Vec<(Vec<HandClass>, f64)>) -> Vec<(Vec<HandClass>, f64)> {
+    let is_single = |run: &(Vec<HandClass>, f64)| run.0.len() == 1 && run.0[0].shape != Shape::Pair;
+    let mut used = vec![false; runs.len()];
+    let mut merged: Vec<(Vec<HandClass>, f64)> = Vec::new();
+
+    for i in 0..runs.len() {
+        if used[i] {
+            continue;
+        }
+
+        let (mut hands, weight) = runs[i].clone();
+        if is_single(&runs[i]) {
+            while let Some(next) = (i + 1..runs.len()).find(|j| {
+                let last = hands[hands.len() - 1];
+                let hand = runs[*j].0[0];
+
+                !used[*j]
+                    && is_single(&runs[*j])
+                    && runs[*j].1 == weight
+                    && hand.shape == last.shape
+                    && hand.high as u8 + 1 == last.high as u8
+                    && hand.low as u8 + 1 == last.low as u8
+            }) {
+                used[next] = true;
+                hands.push(runs[next].0[0]);
+            }
+        }
+
+        merged.push((hands, weight));
+    }
+
+    merged
+}
+
+// Writes a run as a hand or a span, e.g. `QQ+`, `99-77`, `ATs+` or `76s-54s`.
+fn format_run(hands: &[HandClass]) -> String {
+    let (first, last) = (hands[0], hands[hands.len() - 1]);
+    let top = match first.shape {
+        Shape::Pair => Rank::Ace as u8,
+        _ => first.high as u8 - 1,
+    };
+
+    if hands.len() == 1 {
+        first.to_string()
+    } else if (first.shape == Shape::Pair || first.high == last.high)
+        && first.varying_rank() as u8 == top
+    {
+        format!("{}+", last)
+    } else {
+        format!("{}-{}", first, last)
+    }
+}
+
+fn format_weight(weight: f64) -> String {
+    if weight == 1.0 {
+        String::new()
+    } else {
+        format!(":{}", weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(range: &str) -> String {
+        let parsed: Range = range.parse().unwrap();
+        let written = parsed.to_string();
+
+        assert_eq!(written.parse::<Range>().unwrap(), parsed);
+        written
+    }
+
+    #[test]
+    fn connectors_merge_into_diagonal_spans() {
+        assert_eq!(round_trip("76s-54s"), "76s-54s");
+        assert_eq!(round_trip("KQs, QJs, JTs"), "KQs-JTs");
+        assert_eq!(round_trip("T8o-64o"), "T8o-64o");
+    }
+
+    #[test]
+    fn kicker_and_pair_runs_stay_as_they_were() {
+        assert_eq!(
+            round_trip("QQ+, 99-77, ATs+, A5s-A2s"),
+            "QQ+, 99-77, ATs+, A5s-A2s"
+        );
+        assert_eq!(round_trip("AK, KQs"), "AK, KQs");
+        assert_eq!(
+            round_trip("AA, KQs:0.5, QJs, JTs, AhKh"),
+            "AA, KQs:0.5, QJs-JTs, AhKh"
+        );
+    }
+
+    #[test]
+    fn combos_write_in_compact_notation() {
+        let combo = Combo::new("TD".parse().unwrap(), "AS".parse().unwrap());
+
+        assert_eq!(combo.to_string(), "AsTd");
+    }
+}
@@ -0,0 +1,188 @@
+use super::table::{Action, ActionError, ActionRecord, Table, TableConfig};
+
+/// Everything needed to play a hand again: the table before the hand, the
+/// seed the deck was shuffled with and every decision in order. Blinds,
+/// antes and cards follow from the rest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandRecord {
+    pub config: TableConfig,
+    pub stacks: Vec<u64>,
+    pub button: usize,
+    pub seed: u64,
+    pub actions: Vec<Action>,
+}
+
+/// Steps through a recorded hand one action at a time on a fresh [`Table`],
+/// so the state of the table can be inspected after every action.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    record: HandRecord,
+    table: Table,
+    position: usize,
+}
+
+impl Replay {
+    /// Starts the hand, leaving the table with the blinds posted and the hole
+    /// cards dealt.
+    pub fn new(record: HandRecord) -> Result<Self, ActionError> {
+        let table = start(&record)?;
+
+        Ok(Replay {
+            record,
+            table,
+            position: 0,
+        })
+    }
+
+    pub fn record(&self) -> &HandRecord {
+        &self.record
+    }
+
+    pub fn table(&self) -> &Table {
+        &self.table
+    }
+
+    /// Number of recorded actions applied so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.record.actions.len()
+    }
+
+    /// Applies the next recorded action and returns it, or `None` once every
+    /// action was applied.
+    pub fn step(&mut self) -> Result<Option<ActionRecord>, ActionError> {
+        let action = match self.record.actions.get(self.position) {
+            Some(action) => *action,
+            None => return Ok(None),
+        };
+
+        self.table.act(action)?;
+        self.position += 1;
+
+        Ok(self.table.actions().last().copied())
+    }
+
+    /// Moves to the state after the first `position` actions, going back to
+    /// the start of the hand when rewinding.
+    pub fn seek(&mut self, position: usize) -> Result<(), ActionError> {
+        if position < self.position {
+            self.table = start(&self.record)?;
+            self.position = 0;
+        }
+
+        while self.position < position.min(self.record.actions.len()) {
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    /// Plays the rest of the hand.
+    pub fn finish(&mut self) -> Result<(), ActionError> {
+        self.seek(self.record.actions.len())
+    }
+}
+
+fn start(record: &HandRecord) -> Result<Table, ActionError> {
+    let mut table = Table::new(record.config, &record.stacks, record.button);
+    table.start_hand(record.seed)?;
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::table::{Limit, Street};
+
+    fn config() -> TableConfig {
+        TableConfig {
+            small_blind: 10,
+            big_blind: 20,
+            ante: 0,
+            limit: Limit::NoLimit,
+        }
+    }
+
+    // Plays a hand on a table and returns the table with the hand over.
+    fn played(actions: &[Action]) -> Table {
+        let mut table = Table::new(config(), &[500, 1500, 3000], 0);
+        table.start_hand(5).unwrap();
+        for action in actions {
+            table.act(*action).unwrap();
+        }
+        table
+    }
+
+    #[test]
+    fn replays_the_table_at_every_step() {
+        let actions = [
+            Action::Raise(60),
+            Action::Call,
+            Action::Call,
+            Action::Check,
+            Action::Bet(100),
+            Action::Fold,
+            Action::Call,
+        ];
+        let table = played(&actions);
+        let mut replay = Replay::new(table.hand_record().unwrap()).unwrap();
+
+        assert_eq!(replay.position(), 0);
+        assert_eq!(replay.table().pot(), 30);
+        assert_eq!(replay.table().to_act(), Some(0));
+
+        for (i, action) in actions.iter().enumerate() {
+            let record = replay.step().unwrap().unwrap();
+            assert_eq!(record, table.actions()[i]);
+            assert_eq!(record.action, *action);
+            assert_eq!(replay.position(), i + 1);
+        }
+        assert!(replay.is_finished());
+        assert_eq!(replay.step().unwrap(), None);
+
+        assert_eq!(replay.table().board(), table.board());
+        assert_eq!(replay.table().seats(), table.seats());
+        assert_eq!(replay.table().payouts(), table.payouts());
+    }
+
+    #[test]
+    fn seeking_back_starts_over() {
+        let table = played(&[
+            Action::Raise(500),
+            Action::Call,
+            Action::Raise(3000),
+            Action::Call,
+        ]);
+        let mut replay = Replay::new(table.hand_record().unwrap()).unwrap();
+
+        replay.seek(2).unwrap();
+        assert_eq!(replay.table().pot(), 1020);
+        assert_eq!(replay.table().street(), Street::Preflop);
+
+        replay.finish().unwrap();
+        assert_eq!(replay.table().street(), Street::Showdown);
+        assert_eq!(replay.table().seats(), table.seats());
+        assert_eq!(replay.table().pots(), table.pots());
+
+        replay.seek(1).unwrap();
+        let mut fresh = Replay::new(table.hand_record().unwrap()).unwrap();
+        fresh.step().unwrap();
+        assert_eq!(replay.position(), 1);
+        assert_eq!(replay.table().seats(), fresh.table().seats());
+    }
+
+    #[test]
+    fn illegal_actions_stop_the_replay() {
+        let mut record = played(&[Action::Fold, Action::Fold]).hand_record().unwrap();
+        record.actions.insert(1, Action::Check);
+        let mut replay = Replay::new(record).unwrap();
+
+        replay.step().unwrap();
+        assert_eq!(replay.step(), Err(ActionError::CannotCheck { to_call: 10 }));
+        assert_eq!(replay.position(), 1);
+    }
+}
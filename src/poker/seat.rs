@@ -0,0 +1,164 @@
+use super::{
+    pots::{settle, OddChipRule, Pot, Settlement},
+    Card, HandRank, RankingRules,
+};
+
+/// A player's chips and their part in the current hand, shared by the
+/// Hold'em table and the fixed-limit games.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    pub(super) stack: u64,
+    pub(super) hole_cards: Option<[Card; 2]>,
+    pub(super) street_bet: u64,
+    pub(super) contributed: u64,
+    pub(super) in_hand: bool,
+    pub(super) folded: bool,
+    pub(super) acted: bool,
+    pub(super) may_raise: bool,
+}
+
+impl Seat {
+    pub(super) fn new(stack: u64) -> Self {
+        Seat {
+            stack,
+            hole_cards: None,
+            street_bet: 0,
+            contributed: 0,
+            in_hand: false,
+            folded: false,
+            acted: false,
+            may_raise: false,
+        }
+    }
+
+    pub fn stack(&self) -> u64 {
+        self.stack
+    }
+
+    /// The Hold'em hole cards, `None` in draw and stud where the game holds
+    /// the cards.
+    pub fn hole_cards(&self) -> Option<[Card; 2]> {
+        self.hole_cards
+    }
+
+    /// Chips put in during the current street or betting round.
+    pub fn street_bet(&self) -> u64 {
+        self.street_bet
+    }
+
+    /// Chips put in over the whole hand, antes included.
+    pub fn contributed(&self) -> u64 {
+        self.contributed
+    }
+
+    /// Whether the seat was dealt into the current hand and has not folded.
+    pub fn is_live(&self) -> bool {
+        self.in_hand && !self.folded
+    }
+
+    pub fn is_all_in(&self) -> bool {
+        self.is_live() && self.stack == 0
+    }
+
+    pub(super) fn can_act(&self) -> bool {
+        self.is_live() && self.stack > 0
+    }
+
+    pub(super) fn put_in(&mut self, amount: u64) -> u64 {
+        let amount = amount.min(self.stack);
+
+        self.stack -= amount;
+        self.street_bet += amount;
+        self.contributed += amount;
+
+        amount
+    }
+
+    // Clears the last hand and deals the seat in when it has chips.
+    pub(super) fn start_hand(&mut self) {
+        *self = Seat {
+            in_hand: self.stack > 0,
+            ..Seat::new(self.stack)
+        };
+    }
+
+    // An ante goes in the pot without counting as a bet.
+    pub(super) fn post_ante(&mut self, amount: u64) -> u64 {
+        let ante = self.put_in(amount);
+        self.street_bet -= ante;
+
+        ante
+    }
+}
+
+// Gives back the part of the largest bet of the round that nobody else
+// matched.
+pub(super) fn return_uncalled_bet(seats: &mut [Seat]) {
+    let mut bets: Vec<(u64, usize)> = seats
+        .iter()
+        .enumerate()
+        .map(|(i, seat)| (seat.street_bet, i))
+        .collect();
+    bets.sort_unstable_by(|a, b| b.cmp(a));
+
+    let (largest, seat) = bets[0];
+    let second = bets.get(1).map_or(0, |bet| bet.0);
+    let uncalled = largest - second;
+
+    let seat = &mut seats[seat];
+    seat.stack += uncalled;
+    seat.street_bet -= uncalled;
+    seat.contributed -= uncalled;
+}
+
+// Ends the hand and adds the winnings to the stacks. The last live seat
+// takes every chip uncontested; otherwise the pots are settled between the
+// hands shown, odd chips going left of the button.
+pub(super) fn finish_hand(
+    seats: &mut [Seat],
+    hand_ranks: &[Option<HandRank>],
+    button: usize,
+) -> Settlement {
+    let live: Vec<usize> = (0..seats.len()).filter(|i| seats[*i].is_live()).collect();
+
+    let settlement = match live[..] {
+        [winner] => {
+            let pot = seats.iter().map(|seat| seat.contributed).sum();
+            let mut payouts = vec![0; seats.len()];
+            payouts[winner] = pot;
+
+            Settlement {
+                pots: vec![Pot {
+                    amount: pot,
+                    eligible: vec![winner],
+                    winners: vec![winner],
+                }],
+                payouts,
+            }
+        }
+        _ => {
+            let contributions: Vec<u64> = seats.iter().map(|seat| seat.contributed).collect();
+            settle(
+                &contributions,
+                hand_ranks,
+                RankingRules::High,
+                OddChipRule::LeftOfButton(button),
+            )
+        }
+    };
+
+    for (seat, payout) in seats.iter_mut().zip(&settlement.payouts) {
+        seat.stack += payout;
+    }
+
+    settlement
+}
+
+// First seat after `from`, going left and wrapping around, that matches the
+// predicate, or `from` itself when none does.
+pub(super) fn next_seat<F: Fn(&Seat) -> bool>(seats: &[Seat], from: usize, predicate: F) -> usize {
+    (1..=seats.len())
+        .map(|offset| (from + offset) % seats.len())
+        .find(|seat| predicate(&seats[*seat]))
+        .unwrap_or(from)
+}
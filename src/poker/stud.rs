@@ -0,0 +1,419 @@
+use super::{
+    best_hand,
+    deck::{Deck, DeckError},
+    evaluate_partial,
+    limit::{LimitBetting, LimitConfig},
+    pots::Pot,
+    seat::Seat,
+    table::{Action, ActionError, LegalActions},
+    Card, HandRank,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StudStreet {
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Showdown,
+}
+
+/// Seven card stud with fixed-limit betting. Each player gets two down-cards
+/// and an up-card, then three more up-cards and a last down-card, with a
+/// betting round after each deal.
+///
+/// The lowest up-card brings it in on third street, suits breaking ties from
+/// clubs up. On later streets the best hand showing acts first. When the deck
+/// runs short for the last card, a single community card is dealt instead.
+#[derive(Debug, Clone)]
+pub struct StudGame {
+    config: LimitConfig,
+    betting: LimitBetting,
+    dealer: usize,
+    deck: Deck,
+    down_cards: Vec<Vec<Card>>,
+    up_cards: Vec<Vec<Card>>,
+    community: Option<Card>,
+    street: StudStreet,
+    bring_in: Option<usize>,
+    in_progress: bool,
+}
+
+impl StudGame {
+    pub fn new(config: LimitConfig, stacks: &[u64], dealer: usize) -> Self {
+        StudGame {
+            config,
+            betting: LimitBetting::new(stacks, config.max_bets),
+            dealer,
+            deck: Deck::default(),
+            down_cards: vec![Vec::new(); stacks.len()],
+            up_cards: vec![Vec::new(); stacks.len()],
+            community: None,
+            street: StudStreet::Third,
+            bring_in: None,
+            in_progress: false,
+        }
+    }
+
+    pub fn config(&self) -> &LimitConfig {
+        &self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        self.betting.seats()
+    }
+
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    pub fn street(&self) -> StudStreet {
+        self.street
+    }
+
+    /// Cards only the seat sees.
+    pub fn down_cards(&self, seat: usize) -> &[Card] {
+        &self.down_cards[seat]
+    }
+
+    /// Cards every player sees, in dealing order.
+    pub fn up_cards(&self, seat: usize) -> &[Card] {
+        &self.up_cards[seat]
+    }
+
+    /// The shared last card, when the deck ran short.
+    pub fn community(&self) -> Option<Card> {
+        self.community
+    }
+
+    /// The seat that brought it in on third street.
+    pub fn bring_in(&self) -> Option<usize> {
+        self.bring_in
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.betting.to_act()
+    }
+
+    pub fn is_hand_in_progress(&self) -> bool {
+        self.in_progress
+    }
+
+    pub fn pot(&self) -> u64 {
+        self.betting.pot()
+    }
+
+    pub fn pots(&self) -> &[Pot] {
+        self.betting.pots()
+    }
+
+    pub fn payouts(&self) -> &[u64] {
+        self.betting.payouts()
+    }
+
+    /// Shuffles a new deck with the given seed, takes the antes, deals third
+    /// street and posts the bring-in. More than eight seats with chips is an
+    /// error, and leaves the game as it was.
+    pub fn start_hand(&mut self, seed: u64) -> Result<(), ActionError> {
+        if self.in_progress {
+            return Err(ActionError::HandInProgress);
+        }
+        let players = self.seats().iter().filter(|seat| seat.stack() > 0).count();
+        if players < 2 {
+            return Err(ActionError::NotEnoughPlayers);
+        }
+
+        // Every seat dealt in needs six cards from a single deck, the seventh
+        // street falling back on a community card.
+        let deck = Deck::shuffled(seed);
+        if players * 6 > deck.len() {
+            return Err(DeckError::NotEnoughCards {
+                requested: players * 6,
+                remaining: deck.len(),
+            }
+            .into());
+        }
+
+        self.betting.start_hand();
+        self.deck = deck;
+        self.down_cards = vec![Vec::new(); self.down_cards.len()];
+        self.up_cards = vec![Vec::new(); self.up_cards.len()];
+        self.community = None;
+        self.street = StudStreet::Third;
+        self.in_progress = true;
+
+        let order = self.live_seats();
+        for seat in &order {
+            self.betting.post_ante(*seat, self.config.ante);
+        }
+
+        for round in 0..3 {
+            for seat in &order {
+                let card = self.deck.deal_cards(1)?;
+                if round < 2 {
+                    self.down_cards[*seat].extend(card);
+                } else {
+                    self.up_cards[*seat].extend(card);
+                }
+            }
+        }
+
+        // Lowest rank, then lowest suit.
+        let bring_in = *order
+            .iter()
+            .min_by_key(|seat| {
+                let card = self.up_cards[**seat][0];
+                (card.rank, card.suit)
+            })
+            .unwrap();
+        self.bring_in = Some(bring_in);
+
+        self.betting
+            .start_round(self.config.small_bet, bring_in + 1);
+        self.betting.post_bring_in(bring_in, self.config.bring_in);
+        self.advance()
+    }
+
+    /// What the player to act may do, or `None` when nobody is to act.
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        self.betting.legal_actions()
+    }
+
+    /// Applies an action for the player to act. On third street, raising
+    /// the bring-in completes it to the small bet.
+    pub fn act(&mut self, action: Action) -> Result<(), ActionError> {
+        if !self.in_progress {
+            return Err(ActionError::NoHandInProgress);
+        }
+
+        self.betting.act(action)?;
+        self.advance()
+    }
+
+    // Deals the next streets until a player has to act or the hand is over.
+    fn advance(&mut self) -> Result<(), ActionError> {
+        loop {
+            if self.betting.live_count() == 1 {
+                self.betting.return_uncalled_bet();
+                self.finish_hand(&[]);
+                return Ok(());
+            }
+            if self.betting.to_act().is_some() {
+                return Ok(());
+            }
+
+            self.betting.return_uncalled_bet();
+
+            self.street = match self.street {
+                StudStreet::Third => StudStreet::Fourth,
+                StudStreet::Fourth => StudStreet::Fifth,
+                StudStreet::Fifth => StudStreet::Sixth,
+                StudStreet::Sixth => StudStreet::Seventh,
+                StudStreet::Seventh | StudStreet::Showdown => {
+                    self.showdown();
+                    return Ok(());
+                }
+            };
+            self.deal_street()?;
+
+            let bet_size = match self.street {
+                StudStreet::Fourth => self.config.small_bet,
+                _ => self.config.big_bet,
+            };
+            let first = self.best_showing();
+            self.betting.start_round(bet_size, first);
+        }
+    }
+
+    fn deal_street(&mut self) -> Result<(), ActionError> {
+        let order = self.live_seats();
+
+        if self.street == StudStreet::Seventh && self.deck.len() < order.len() {
+            self.community = Some(self.deck.deal_cards(1)?[0]);
+            return Ok(());
+        }
+
+        if self.deck.len() > order.len() {
+            self.deck.burn()?;
+        }
+
+        for seat in order {
+            let card = self.deck.deal_cards(1)?;
+            if self.street == StudStreet::Seventh {
+                self.down_cards[seat].extend(card);
+            } else {
+                self.up_cards[seat].extend(card);
+            }
+        }
+
+        Ok(())
+    }
+
+    // The live seat with the best hand showing, the first one left of the
+    // dealer on a tie.
+    fn best_showing(&self) -> usize {
+        let mut best: Option<(HandRank, usize)> = None;
+
+        for seat in self.live_seats() {
+            let rank = evaluate_partial(&self.up_cards[seat]);
+            if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+                best = Some((rank, seat));
+            }
+        }
+
+        best.map_or(0, |(_, seat)| seat)
+    }
+
+    fn showdown(&mut self) {
+        self.street = StudStreet::Showdown;
+
+        let hand_ranks: Vec<Option<HandRank>> = (0..self.seats().len())
+            .map(|seat| {
+                if !self.seats()[seat].is_live() {
+                    return None;
+                }
+
+                let mut cards = self.down_cards[seat].clone();
+                cards.extend_from_slice(&self.up_cards[seat]);
+                cards.extend(self.community);
+                Some(best_hand(&cards).0)
+            })
+            .collect();
+
+        self.finish_hand(&hand_ranks);
+    }
+
+    fn finish_hand(&mut self, hand_ranks: &[Option<HandRank>]) {
+        self.betting.finish_hand(hand_ranks, self.dealer);
+        self.in_progress = false;
+        self.dealer = self.betting.next_with_chips(self.dealer);
+    }
+
+    // Seats still in the hand, starting left of the dealer.
+    fn live_seats(&self) -> Vec<usize> {
+        let seats = self.seats();
+
+        (1..=seats.len())
+            .map(|offset| (self.dealer + offset) % seats.len())
+            .filter(|seat| seats[*seat].is_live())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::{parse_hand, HandType, Rank};
+
+    const CONFIG: LimitConfig = LimitConfig {
+        ante: 1,
+        bring_in: 1,
+        small_bet: 2,
+        big_bet: 4,
+        max_bets: 4,
+    };
+
+    // Checks or calls until the street changes or the hand is over.
+    fn call_round(game: &mut StudGame) {
+        let street = game.street();
+        while game.is_hand_in_progress() && game.street() == street {
+            let legal = game.legal_actions().expect("a seat is to act");
+            let action = if legal.can_check() {
+                Action::Check
+            } else {
+                Action::Call
+            };
+            game.act(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn lowest_up_card_brings_it_in() {
+        for seed in 0..20 {
+            let mut game = StudGame::new(CONFIG, &[100; 5], 0);
+            game.start_hand(seed).unwrap();
+
+            let bring_in = game.bring_in().unwrap();
+            let lowest = (0..5)
+                .map(|seat| game.up_cards(seat)[0])
+                .min_by_key(|card| (card.rank, card.suit))
+                .unwrap();
+            assert_eq!(game.up_cards(bring_in)[0], lowest);
+            assert_eq!(game.seats()[bring_in].street_bet(), CONFIG.bring_in);
+        }
+    }
+
+    #[test]
+    fn best_hand_showing_acts_first_from_fourth_street() {
+        for seed in 0..20 {
+            let mut game = StudGame::new(CONFIG, &[100; 5], 0);
+            game.start_hand(seed).unwrap();
+            call_round(&mut game);
+            assert_eq!(game.street(), StudStreet::Fourth);
+
+            let first = game.to_act().unwrap();
+            let showing = evaluate_partial(game.up_cards(first));
+            for seat in 0..5 {
+                assert!(evaluate_partial(game.up_cards(seat)) <= showing);
+            }
+        }
+    }
+
+    #[test]
+    fn short_deck_deals_a_community_card() {
+        let mut game = StudGame::new(CONFIG, &[100; 8], 0);
+        game.start_hand(3).unwrap();
+        while game.is_hand_in_progress() {
+            call_round(&mut game);
+        }
+
+        // Eight players and the burns use up all but one card by sixth street.
+        assert!(game.community().is_some());
+        assert!((0..8).all(|seat| game.down_cards(seat).len() == 2));
+        assert!((0..8).all(|seat| game.up_cards(seat).len() == 4));
+        // Antes and bring-in calls, every later street checked through.
+        assert_eq!(game.payouts().iter().sum::<u64>(), 16);
+        assert_eq!(
+            game.seats().iter().map(|seat| seat.stack()).sum::<u64>(),
+            800
+        );
+    }
+
+    #[test]
+    fn too_many_players_leave_the_game_untouched() {
+        let mut game = StudGame::new(CONFIG, &[100; 9], 0);
+
+        assert_eq!(
+            game.start_hand(1),
+            Err(ActionError::Deck(DeckError::NotEnoughCards {
+                requested: 54,
+                remaining: 52,
+            }))
+        );
+        assert!(!game.is_hand_in_progress());
+        assert!(game.seats().iter().all(|seat| seat.stack() == 100));
+        assert!((0..9).all(|seat| game.up_cards(seat).is_empty()));
+    }
+
+    #[test]
+    fn partial_hands_count_groups_and_high_cards() {
+        let partial = |cards: &str| evaluate_partial(&parse_hand(cards).unwrap());
+
+        let aces = partial("AS AD KH");
+        assert_eq!(aces.hand_type, HandType::OnePair);
+        assert_eq!(aces.ranks, vec![Rank::Ace, Rank::King]);
+
+        assert_eq!(partial("9S 9D 9H 2C").hand_type, HandType::ThreeOfKind);
+        assert_eq!(partial("5S 5D 4H 4C").hand_type, HandType::TwoPair);
+
+        // Four suited connectors are neither a straight nor a flush.
+        let connectors = partial("AS KS QS JS");
+        assert_eq!(connectors.hand_type, HandType::HighCard);
+        assert!(partial("2S 2D 3H") > connectors);
+    }
+}
@@ -0,0 +1,687 @@
+use std::{error::Error, fmt};
+
+use super::{
+    best_hand,
+    deck::{Deck, DeckError},
+    pots::Pot,
+    replay::HandRecord,
+    seat::{finish_hand, next_seat, return_uncalled_bet, Seat},
+    Card, HandRank,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Limit {
+    #[default]
+    NoLimit,
+    /// Bets and raises are capped at the size of the pot.
+    PotLimit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableConfig {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+    pub limit: Limit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+}
+
+/// A player decision. Bets and raises give the total the player puts in on
+/// the current street, i.e. `Raise(300)` is a raise to 300.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Bet(u64),
+    Raise(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionRecord {
+    pub seat: usize,
+    pub street: Street,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Post {
+    Ante,
+    SmallBlind,
+    BigBlind,
+}
+
+/// Chips a seat put in before the cards were dealt. The amount is less than
+/// the ante or blind when the seat was short of chips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PostRecord {
+    pub seat: usize,
+    pub post: Post,
+    pub amount: u64,
+}
+
+/// What the player to act may do. A player facing no bet can check, and
+/// `min_raise_to` is `None` when betting is closed to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LegalActions {
+    pub to_call: u64,
+    pub min_raise_to: Option<u64>,
+    pub max_raise_to: Option<u64>,
+}
+
+impl LegalActions {
+    pub fn can_check(&self) -> bool {
+        self.to_call == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionError {
+    HandInProgress,
+    NoHandInProgress,
+    NotEnoughPlayers,
+    CannotCheck {
+        to_call: u64,
+    },
+    NothingToCall,
+    BetAlreadyMade,
+    NoBetToRaise,
+    RaiseNotAllowed,
+    InvalidAmount {
+        amount: u64,
+        min: u64,
+        max: u64,
+    },
+    /// The move does not fit the current phase of the hand, e.g. betting
+    /// while players are drawing.
+    WrongPhase,
+    CardNotHeld(Card),
+    Deck(DeckError),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::HandInProgress => write!(f, "a hand is already in progress"),
+            ActionError::NoHandInProgress => write!(f, "no hand is in progress"),
+            ActionError::NotEnoughPlayers => write!(f, "at least two players need chips"),
+            ActionError::CannotCheck { to_call } => {
+                write!(f, "cannot check facing a bet, {} to call", to_call)
+            }
+            ActionError::NothingToCall => write!(f, "there is no bet to call"),
+            ActionError::BetAlreadyMade => write!(f, "a bet was already made, raise instead"),
+            ActionError::NoBetToRaise => write!(f, "there is no bet to raise, bet instead"),
+            ActionError::RaiseNotAllowed => write!(f, "betting is not open to this player"),
+            ActionError::InvalidAmount { amount, min, max } => {
+                write!(f, "{} is not between {} and {}", amount, min, max)
+            }
+            ActionError::WrongPhase => write!(f, "not allowed at this point of the hand"),
+            ActionError::CardNotHeld(card) => write!(f, "the card {} is not held", card),
+            ActionError::Deck(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ActionError {}
+
+impl From<DeckError> for ActionError {
+    fn from(err: DeckError) -> Self {
+        ActionError::Deck(err)
+    }
+}
+
+/// A No-Limit or Pot-Limit Hold'em table. It tracks the seats, blinds, antes
+/// and button, validates each action and moves the hand from street to street
+/// until a single player is left or the showdown is reached.
+#[derive(Debug, Clone)]
+pub struct Table {
+    config: TableConfig,
+    seats: Vec<Seat>,
+    button: usize,
+    // Stacks and button as they were when the last hand started.
+    starting_stacks: Vec<u64>,
+    hand_button: usize,
+    deck: Deck,
+    board: Vec<Card>,
+    street: Street,
+    to_act: Option<usize>,
+    current_bet: u64,
+    last_raise: u64,
+    in_progress: bool,
+    posts: Vec<PostRecord>,
+    actions: Vec<ActionRecord>,
+    pots: Vec<Pot>,
+    payouts: Vec<u64>,
+}
+
+impl Table {
+    pub fn new(config: TableConfig, stacks: &[u64], button: usize) -> Self {
+        Table {
+            config,
+            seats: stacks.iter().map(|stack| Seat::new(*stack)).collect(),
+            button,
+            starting_stacks: stacks.to_vec(),
+            hand_button: button,
+            deck: Deck::default(),
+            board: Vec::new(),
+            street: Street::Preflop,
+            to_act: None,
+            current_bet: 0,
+            last_raise: 0,
+            in_progress: false,
+            posts: Vec::new(),
+            actions: Vec::new(),
+            pots: Vec::new(),
+            payouts: vec![0; stacks.len()],
+        }
+    }
+
+    pub fn config(&self) -> &TableConfig {
+        &self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn button(&self) -> usize {
+        self.button
+    }
+
+    pub fn board(&self) -> &[Card] {
+        &self.board
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    /// The deck of the current hand, with its seed and the cards dealt.
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    pub fn is_hand_in_progress(&self) -> bool {
+        self.in_progress
+    }
+
+    /// Every chip put in the pot during the current hand.
+    pub fn pot(&self) -> u64 {
+        self.seats.iter().map(|seat| seat.contributed).sum()
+    }
+
+    /// Antes and blinds posted in the current or last hand.
+    pub fn posts(&self) -> &[PostRecord] {
+        &self.posts
+    }
+
+    /// Actions taken during the current or last hand, blinds excluded.
+    pub fn actions(&self) -> &[ActionRecord] {
+        &self.actions
+    }
+
+    /// The current or last hand as a [`HandRecord`] that replays it from the
+    /// start, or `None` if no hand was started yet.
+    pub fn hand_record(&self) -> Option<HandRecord> {
+        Some(HandRecord {
+            config: self.config,
+            stacks: self.starting_stacks.clone(),
+            button: self.hand_button,
+            seed: self.deck.seed()?,
+            actions: self.actions.iter().map(|record| record.action).collect(),
+        })
+    }
+
+    /// The main and side pots of the last hand, once it is over.
+    pub fn pots(&self) -> &[Pot] {
+        &self.pots
+    }
+
+    /// Chips each seat won at the end of the last hand.
+    pub fn payouts(&self) -> &[u64] {
+        &self.payouts
+    }
+
+    /// Shuffles a new deck with the given seed, posts antes and blinds and
+    /// deals the hole cards. Seats without chips sit the hand out.
+    pub fn start_hand(&mut self, seed: u64) -> Result<(), ActionError> {
+        if self.in_progress {
+            return Err(ActionError::HandInProgress);
+        }
+        if self.seats.iter().filter(|seat| seat.stack > 0).count() < 2 {
+            return Err(ActionError::NotEnoughPlayers);
+        }
+
+        self.starting_stacks = self.seats.iter().map(|seat| seat.stack).collect();
+        self.hand_button = self.button;
+
+        for seat in self.seats.iter_mut() {
+            seat.start_hand();
+        }
+
+        if !self.seats[self.button].in_hand {
+            self.button = self.next_seat(self.button, |seat| seat.in_hand);
+        }
+
+        self.deck = Deck::shuffled(seed);
+        self.board.clear();
+        self.posts.clear();
+        self.actions.clear();
+        self.pots.clear();
+        self.payouts = vec![0; self.seats.len()];
+        self.street = Street::Preflop;
+        self.in_progress = true;
+
+        for (i, seat) in self.seats.iter_mut().enumerate() {
+            if seat.in_hand && self.config.ante > 0 {
+                let ante = seat.post_ante(self.config.ante);
+                self.posts.push(PostRecord {
+                    seat: i,
+                    post: Post::Ante,
+                    amount: ante,
+                });
+            }
+        }
+
+        let heads_up = self.seats.iter().filter(|seat| seat.in_hand).count() == 2;
+        let small_blind = if heads_up {
+            self.button
+        } else {
+            self.next_seat(self.button, |seat| seat.in_hand)
+        };
+        let big_blind = self.next_seat(small_blind, |seat| seat.in_hand);
+
+        for (seat, post, blind) in [
+            (small_blind, Post::SmallBlind, self.config.small_blind),
+            (big_blind, Post::BigBlind, self.config.big_blind),
+        ] {
+            let amount = self.seats[seat].put_in(blind);
+            self.posts.push(PostRecord { seat, post, amount });
+        }
+        self.current_bet = self.config.big_blind;
+        self.last_raise = self.config.big_blind;
+
+        // One card at a time, starting left of the button.
+        let order: Vec<usize> = (1..=self.seats.len())
+            .map(|offset| (self.button + offset) % self.seats.len())
+            .filter(|seat| self.seats[*seat].in_hand)
+            .collect();
+        let first = self.deck.deal_cards(order.len())?;
+        let second = self.deck.deal_cards(order.len())?;
+
+        for (i, seat) in order.iter().enumerate() {
+            self.seats[*seat].hole_cards = Some([first[i], second[i]]);
+        }
+
+        for seat in self.seats.iter_mut().filter(|seat| seat.in_hand) {
+            seat.may_raise = true;
+        }
+
+        self.to_act = Some(self.next_to_act(big_blind));
+        self.settle_round()
+    }
+
+    /// What the player to act may do, or `None` when nobody is to act.
+    pub fn legal_actions(&self) -> Option<LegalActions> {
+        let seat = &self.seats[self.to_act?];
+        let to_call = (self.current_bet - seat.street_bet).min(seat.stack);
+        let all_in_to = seat.street_bet + seat.stack;
+
+        let others_can_act = self
+            .seats
+            .iter()
+            .enumerate()
+            .any(|(i, other)| Some(i) != self.to_act && other.can_act());
+
+        if !seat.may_raise || !others_can_act || seat.stack <= to_call {
+            return Some(LegalActions {
+                to_call,
+                min_raise_to: None,
+                max_raise_to: None,
+            });
+        }
+
+        let min_raise_to = if self.current_bet == 0 {
+            self.config.big_blind
+        } else {
+            self.current_bet + self.last_raise
+        };
+
+        let max_raise_to = match self.config.limit {
+            Limit::NoLimit => all_in_to,
+            Limit::PotLimit => all_in_to.min(self.current_bet + self.pot() + to_call),
+        };
+
+        Some(LegalActions {
+            to_call,
+            min_raise_to: Some(min_raise_to.min(all_in_to)),
+            max_raise_to: Some(max_raise_to.max(min_raise_to.min(all_in_to))),
+        })
+    }
+
+    /// Applies an action for the player to act.
+    pub fn act(&mut self, action: Action) -> Result<(), ActionError> {
+        let (index, legal) = match (self.to_act, self.legal_actions()) {
+            (Some(index), Some(legal)) => (index, legal),
+            _ => return Err(ActionError::NoHandInProgress),
+        };
+
+        match action {
+            Action::Fold => self.seats[index].folded = true,
+            Action::Check => {
+                if !legal.can_check() {
+                    return Err(ActionError::CannotCheck {
+                        to_call: legal.to_call,
+                    });
+                }
+            }
+            Action::Call => {
+                if legal.can_check() {
+                    return Err(ActionError::NothingToCall);
+                }
+                self.seats[index].put_in(legal.to_call);
+            }
+            Action::Bet(amount) | Action::Raise(amount) => {
+                match (action, self.current_bet) {
+                    (Action::Bet(_), bet) if bet > 0 => return Err(ActionError::BetAlreadyMade),
+                    (Action::Raise(_), 0) => return Err(ActionError::NoBetToRaise),
+                    _ => {}
+                }
+
+                let (min, max) = match (legal.min_raise_to, legal.max_raise_to) {
+                    (Some(min), Some(max)) => (min, max),
+                    _ => return Err(ActionError::RaiseNotAllowed),
+                };
+                if amount < min || amount > max {
+                    return Err(ActionError::InvalidAmount { amount, min, max });
+                }
+
+                let seat = &mut self.seats[index];
+                seat.put_in(amount - seat.street_bet);
+
+                let raise = amount - self.current_bet;
+                let is_full_raise = raise >= self.last_raise;
+                self.current_bet = amount;
+
+                for (i, other) in self.seats.iter_mut().enumerate() {
+                    if i != index && other.can_act() {
+                        other.acted = false;
+                        other.may_raise |= is_full_raise;
+                    }
+                }
+                if is_full_raise {
+                    self.last_raise = raise;
+                }
+            }
+        }
+
+        let seat = &mut self.seats[index];
+        seat.acted = true;
+        seat.may_raise = false;
+
+        self.actions.push(ActionRecord {
+            seat: index,
+            street: self.street,
+            action,
+        });
+
+        self.to_act = Some(self.next_to_act(index));
+        self.settle_round()
+    }
+
+    // Ends the hand, moves to the next street or passes the action on,
+    // depending on who is left to act.
+    fn settle_round(&mut self) -> Result<(), ActionError> {
+        let live = self.seats.iter().filter(|seat| seat.is_live()).count();
+
+        if live == 1 {
+            return_uncalled_bet(&mut self.seats);
+            self.finish_hand(&[]);
+            return Ok(());
+        }
+
+        let current_bet = self.current_bet;
+        let round_complete = self
+            .seats
+            .iter()
+            .filter(|seat| seat.can_act())
+            .all(|seat| seat.acted && seat.street_bet == current_bet);
+
+        // Only one player with chips left, who already matched the bet.
+        let nobody_to_bet = self.seats.iter().filter(|seat| seat.can_act()).count() <= 1
+            && self
+                .seats
+                .iter()
+                .filter(|seat| seat.can_act())
+                .all(|seat| seat.street_bet >= current_bet);
+
+        if !round_complete && !nobody_to_bet {
+            return Ok(());
+        }
+
+        return_uncalled_bet(&mut self.seats);
+
+        loop {
+            for seat in self.seats.iter_mut() {
+                seat.street_bet = 0;
+                seat.acted = false;
+                seat.may_raise = seat.can_act();
+            }
+            self.current_bet = 0;
+            self.last_raise = self.config.big_blind;
+
+            let cards = match self.street {
+                Street::Preflop => 3,
+                Street::Flop | Street::Turn => 1,
+                Street::River | Street::Showdown => 0,
+            };
+
+            if cards == 0 {
+                self.showdown();
+                return Ok(());
+            }
+
+            self.deck.burn()?;
+            self.board.extend(self.deck.deal_cards(cards)?);
+            self.street = match self.street {
+                Street::Preflop => Street::Flop,
+                Street::Flop => Street::Turn,
+                _ => Street::River,
+            };
+
+            if self.seats.iter().filter(|seat| seat.can_act()).count() >= 2 {
+                self.to_act = Some(self.next_to_act(self.button));
+                return Ok(());
+            }
+        }
+    }
+
+    fn showdown(&mut self) {
+        self.street = Street::Showdown;
+
+        let hand_ranks: Vec<Option<HandRank>> = self
+            .seats
+            .iter()
+            .map(|seat| match seat.hole_cards {
+                Some(hole_cards) if seat.is_live() => {
+                    let mut cards = hole_cards.to_vec();
+                    cards.extend_from_slice(&self.board);
+                    Some(best_hand(&cards).0)
+                }
+                _ => None,
+            })
+            .collect();
+
+        self.finish_hand(&hand_ranks);
+    }
+
+    fn finish_hand(&mut self, hand_ranks: &[Option<HandRank>]) {
+        let settlement = finish_hand(&mut self.seats, hand_ranks, self.button);
+
+        self.pots = settlement.pots;
+        self.payouts = settlement.payouts;
+        self.to_act = None;
+        self.in_progress = false;
+        self.button = self.next_seat(self.button, |seat| seat.stack > 0);
+    }
+
+    // First seat after `from` that still has to act on the current bet.
+    fn next_to_act(&self, from: usize) -> usize {
+        let current_bet = self.current_bet;
+
+        self.next_seat(from, |seat| {
+            seat.can_act() && (!seat.acted || seat.street_bet < current_bet)
+        })
+    }
+
+    fn next_seat<F: Fn(&Seat) -> bool>(&self, from: usize, predicate: F) -> usize {
+        next_seat(&self.seats, from, predicate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Three seats with the button on the first one, so it acts first
+    // before the flop.
+    fn table(limit: Limit, stacks: &[u64]) -> Table {
+        let config = TableConfig {
+            small_blind: 10,
+            big_blind: 20,
+            ante: 0,
+            limit,
+        };
+        let mut table = Table::new(config, stacks, 0);
+        table.start_hand(9).unwrap();
+        table
+    }
+
+    fn legal(to_call: u64, min_raise_to: Option<u64>, max_raise_to: Option<u64>) -> LegalActions {
+        LegalActions {
+            to_call,
+            min_raise_to,
+            max_raise_to,
+        }
+    }
+
+    #[test]
+    fn no_limit_raises_are_at_least_the_last_raise() {
+        let mut table = table(Limit::NoLimit, &[1000, 1000, 1000]);
+        assert_eq!(table.to_act(), Some(0));
+        assert_eq!(table.legal_actions(), Some(legal(20, Some(40), Some(1000))));
+
+        assert_eq!(
+            table.act(Action::Raise(30)),
+            Err(ActionError::InvalidAmount {
+                amount: 30,
+                min: 40,
+                max: 1000
+            })
+        );
+        assert_eq!(table.act(Action::Bet(60)), Err(ActionError::BetAlreadyMade));
+        assert_eq!(
+            table.act(Action::Check),
+            Err(ActionError::CannotCheck { to_call: 20 })
+        );
+
+        // A raise of 80 makes 180 the smallest raise after it.
+        table.act(Action::Raise(100)).unwrap();
+        assert_eq!(
+            table.legal_actions(),
+            Some(legal(90, Some(180), Some(1000)))
+        );
+    }
+
+    #[test]
+    fn short_all_in_raises_do_not_reopen_the_betting() {
+        let mut table = table(Limit::NoLimit, &[1000, 130, 1000]);
+        table.act(Action::Raise(100)).unwrap();
+
+        // The small blind can only go all-in for 30 more, less than a raise.
+        assert_eq!(table.legal_actions(), Some(legal(90, Some(130), Some(130))));
+        table.act(Action::Raise(130)).unwrap();
+
+        // The big blind has not acted yet and may still raise by 80.
+        assert_eq!(
+            table.legal_actions(),
+            Some(legal(110, Some(210), Some(1000)))
+        );
+        table.act(Action::Call).unwrap();
+
+        // The first raiser may only call the extra 30 or fold.
+        assert_eq!(table.to_act(), Some(0));
+        assert_eq!(table.legal_actions(), Some(legal(30, None, None)));
+        assert_eq!(
+            table.act(Action::Raise(400)),
+            Err(ActionError::RaiseNotAllowed)
+        );
+        table.act(Action::Call).unwrap();
+        assert_eq!(table.street(), Street::Flop);
+    }
+
+    #[test]
+    fn pot_limit_raises_are_capped_at_the_pot() {
+        let mut table = table(Limit::PotLimit, &[1000, 1000, 1000]);
+
+        // Calling 20 makes the pot 50, so the raise is at most 50 more.
+        assert_eq!(table.legal_actions(), Some(legal(20, Some(40), Some(70))));
+        table.act(Action::Raise(70)).unwrap();
+
+        // Calling 60 makes the pot 160.
+        assert_eq!(table.legal_actions(), Some(legal(60, Some(120), Some(230))));
+        assert_eq!(
+            table.act(Action::Raise(231)),
+            Err(ActionError::InvalidAmount {
+                amount: 231,
+                min: 120,
+                max: 230
+            })
+        );
+        table.act(Action::Raise(230)).unwrap();
+        assert_eq!(table.pot(), 320);
+    }
+
+    #[test]
+    fn uncalled_bets_go_back() {
+        let mut table = table(Limit::NoLimit, &[1000, 1000, 1000]);
+        table.act(Action::Raise(300)).unwrap();
+        table.act(Action::Fold).unwrap();
+        table.act(Action::Fold).unwrap();
+
+        assert!(!table.is_hand_in_progress());
+        assert_eq!(table.payouts(), [50, 0, 0]);
+        let stacks: Vec<u64> = table.seats().iter().map(|seat| seat.stack()).collect();
+        assert_eq!(stacks, [1030, 990, 980]);
+    }
+
+    #[test]
+    fn an_all_in_is_only_called_up_to_the_shorter_stack() {
+        let mut table = table(Limit::NoLimit, &[1000, 400, 1000]);
+        table.act(Action::Raise(1000)).unwrap();
+        table.act(Action::Call).unwrap();
+        table.act(Action::Fold).unwrap();
+
+        // The 600 nobody could call never reaches a pot.
+        assert_eq!(table.street(), Street::Showdown);
+        assert_eq!(table.pots().iter().map(|pot| pot.amount).sum::<u64>(), 820);
+        assert_eq!(table.payouts().iter().sum::<u64>(), 820);
+
+        let stacks: Vec<u64> = table.seats().iter().map(|seat| seat.stack()).collect();
+        assert_eq!(stacks.iter().sum::<u64>(), 2400);
+        assert!(stacks[0] >= 600);
+        assert_eq!(stacks[2], 980);
+    }
+}
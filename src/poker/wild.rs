@@ -0,0 +1,229 @@
+use super::{
+    deck::DeckCard, for_each_combination, get_best_seats, get_hand_rank, Card, HandRank, Rank,
+    RankingRules, Suit,
+};
+
+/// Which cards are wild. Jokers always are, and any card of one of `ranks`
+/// is too, e.g. the deuces in deuces wild.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct WildCards {
+    pub ranks: Vec<Rank>,
+}
+
+impl WildCards {
+    /// Only the jokers are wild.
+    pub fn jokers() -> Self {
+        WildCards::default()
+    }
+
+    /// Deuces wild, along with any jokers.
+    pub fn deuces() -> Self {
+        WildCards {
+            ranks: vec![Rank::Two],
+        }
+    }
+
+    pub fn is_wild(&self, card: DeckCard) -> bool {
+        match card {
+            DeckCard::Joker => true,
+            DeckCard::Card(card) => self.ranks.contains(&card.rank),
+        }
+    }
+}
+
+/// A hand played with wild cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WildHand {
+    pub rank: HandRank,
+    /// The five cards as played, with each wild card replaced by what it
+    /// counted as.
+    pub cards: [Card; 5],
+    /// Every wild card of the hand and the card it counted as.
+    pub substitutions: Vec<(DeckCard, Card)>,
+}
+
+/// Evaluates a five card hand, choosing for each wild card the card that
+/// makes the strongest hand. A wild card may stand for a card already in the
+/// hand, so five of a kind can be made; it ranks above a straight flush.
+/// Hands of the same rank tie whether or not they use wild cards.
+pub fn evaluate_wild(hand: &[DeckCard], wild: &WildCards) -> WildHand {
+    assert_eq!(hand.len(), 5, "a poker hand has exactly five cards");
+
+    let naturals: Vec<Card> = hand
+        .iter()
+        .filter(|card| !wild.is_wild(**card))
+        .filter_map(|card| card.card())
+        .collect();
+    let wild_cards: Vec<DeckCard> = hand
+        .iter()
+        .filter(|card| wild.is_wild(**card))
+        .copied()
+        .collect();
+
+    // Suits only matter for flushes, so the wild cards all take the suit of
+    // the natural cards when they share one.
+    let suit = match naturals.first() {
+        Some(first) if naturals.iter().all(|card| card.suit == first.suit) => first.suit,
+        _ => Suit::Spades,
+    };
+
+    let mut best: Option<(HandRank, Vec<Card>)> = None;
+
+    // Ranks go from the ace down and never increase from one wild card to the
+    // next, since the order they are given in does not matter.
+    for_each_rank_multiset(wild_cards.len(), |ranks| {
+        let substitutes: Vec<Card> = ranks
+            .iter()
+            .map(|i| Card::new(Rank::ALL[Rank::ALL.len() - 1 - i], suit))
+            .collect();
+
+        let mut cards = naturals.clone();
+        cards.extend_from_slice(&substitutes);
+        let rank = get_hand_rank(RankingRules::High, &cards);
+
+        if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+            best = Some((rank, substitutes));
+        }
+    });
+
+    let (rank, substitutes) = best.unwrap();
+    let mut substitutes = substitutes.into_iter();
+
+    let cards: Vec<Card> = hand
+        .iter()
+        .map(|card| match card.card() {
+            Some(card) if !wild.is_wild(DeckCard::Card(card)) => card,
+            _ => substitutes.next().unwrap(),
+        })
+        .collect();
+    let substitutions = hand
+        .iter()
+        .zip(&cards)
+        .filter(|(card, _)| wild.is_wild(**card))
+        .map(|(card, played)| (*card, *played))
+        .collect();
+
+    WildHand {
+        rank,
+        cards: [cards[0], cards[1], cards[2], cards[3], cards[4]],
+        substitutions,
+    }
+}
+
+/// Picks the strongest wild card hand that can be made from five or more
+/// cards.
+pub fn best_wild_hand(cards: &[DeckCard], wild: &WildCards) -> WildHand {
+    assert!(
+        cards.len() >= 5,
+        "at least five cards are needed to make a hand"
+    );
+
+    let mut best: Option<WildHand> = None;
+
+    for_each_combination(cards.len(), 5, |indexes| {
+        let hand: Vec<DeckCard> = indexes.iter().map(|i| cards[*i]).collect();
+        let played = evaluate_wild(&hand, wild);
+
+        if best.as_ref().is_none_or(|best| played.rank > best.rank) {
+            best = Some(played);
+        }
+    });
+
+    best.unwrap()
+}
+
+/// Returns the players holding the best hand when each plays the best five
+/// of their cards, in seat order when several tie.
+pub fn wild_winners(hands: &[Vec<DeckCard>], wild: &WildCards) -> Vec<usize> {
+    let ranks: Vec<HandRank> = hands
+        .iter()
+        .map(|cards| best_wild_hand(cards, wild).rank)
+        .collect();
+
+    get_best_seats(&ranks)
+}
+
+// Calls `f` with every non-decreasing sequence of `k` indexes into the
+// thirteen ranks, i.e. every multiset of ranks of size `k`.
+fn for_each_rank_multiset<F: FnMut(&[usize])>(k: usize, mut f: F) {
+    let mut indexes = vec![0; k];
+
+    loop {
+        f(&indexes);
+
+        let mut i = k;
+        loop {
+            if i == 0 {
+                return;
+            }
+            i -= 1;
+            if indexes[i] < Rank::ALL.len() - 1 {
+                break;
+            }
+        }
+
+        let next = indexes[i] + 1;
+        for index in indexes[i..].iter_mut() {
+            *index = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::HandType;
+
+    fn deck_cards(cards: &str) -> Vec<DeckCard> {
+        cards
+            .split_whitespace()
+            .map(|card| card.parse().unwrap())
+            .collect()
+    }
+
+    fn card(card: &str) -> Card {
+        card.parse().unwrap()
+    }
+
+    #[test]
+    fn joker_completes_a_straight() {
+        let played = evaluate_wild(&deck_cards("9S 8D 7H 6C JK"), &WildCards::jokers());
+
+        assert_eq!(played.rank.hand_type, HandType::Straight);
+        assert_eq!(played.rank.ranks, vec![Rank::Ten]);
+        assert_eq!(played.substitutions, vec![(DeckCard::Joker, card("TS"))]);
+    }
+
+    #[test]
+    fn joker_completes_a_flush() {
+        let played = evaluate_wild(&deck_cards("AH 9H 7H 4H JK"), &WildCards::jokers());
+
+        assert_eq!(played.rank.hand_type, HandType::Flush);
+        assert!(played.cards.iter().all(|card| card.suit == Suit::Hearts));
+    }
+
+    #[test]
+    fn wild_deuces_make_five_of_a_kind() {
+        let played = evaluate_wild(&deck_cards("KS KD KH 2C 2S"), &WildCards::deuces());
+
+        assert_eq!(played.rank.hand_type, HandType::FiveOfKind);
+        assert_eq!(played.rank.ranks, vec![Rank::King]);
+        assert!(
+            played.rank > evaluate_wild(&deck_cards("AS KS QS JS TS"), &WildCards::deuces()).rank
+        );
+    }
+
+    #[test]
+    fn wild_card_picks_the_strongest_hand() {
+        // Trips, two pair and a full house are all possible.
+        let played = evaluate_wild(&deck_cards("JK 6S 6D 5S 5D"), &WildCards::jokers());
+        assert_eq!(played.rank.hand_type, HandType::FullHouse);
+        assert_eq!(played.rank.ranks, vec![Rank::Six, Rank::Five]);
+
+        // The joker as the ten of hearts makes a straight flush, better than
+        // any flush or trips it could make.
+        let best = best_wild_hand(&deck_cards("JK KH QH JH 9H 2C 2D"), &WildCards::jokers());
+        assert_eq!(best.rank.hand_type, HandType::StraightFlush);
+        assert_eq!(best.rank.ranks, vec![Rank::King]);
+    }
+}